@@ -0,0 +1,624 @@
+use std::fmt;
+
+use clap::Args;
+use pinhole::stops;
+use serde::Serialize;
+
+use crate::units;
+
+// Either a named format or a projection diameter.
+#[derive(Args, Debug)]
+#[group(required = true, multiple = false)]
+pub struct Target {
+    /// Film format, e.g. 6x9 or 4x5 (see `phcalc-cli format`)
+    #[arg(long)]
+    format: Option<String>,
+    /// Desired projection diameter, e.g. 108mm
+    #[arg(long, value_parser = units::length)]
+    projection: Option<f32>,
+}
+
+impl Target {
+    pub fn diameter(&self) -> Result<f32, String> {
+        match (&self.format, self.projection) {
+            (_, Some(d)) => Ok(d),
+            (Some(name), None) => pinhole::formats::find(name)
+                .map(|f| f.projection_diameter())
+                .ok_or_else(|| format!("unknown format '{name}'")),
+            (None, None) => unreachable!("clap requires one of the two"),
+        }
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct OptimalArgs {
+    /// Focal length, e.g. 50mm
+    #[arg(long, value_parser = units::length)]
+    focal: f32,
+    /// Wavelength, e.g. 550nm
+    #[arg(long, default_value = "550nm", value_parser = units::wavelength)]
+    wavelength: f32,
+    /// Rayleigh factor
+    #[arg(long, default_value = "1.56", value_parser = units::factor)]
+    rayleigh: f32,
+    /// Subject distance, e.g. 2m or inf
+    #[arg(long, default_value = "inf", value_parser = units::distance)]
+    distance: f32,
+    /// Filter in front of the pinhole, e.g. "Red 25"; give it more than once
+    /// for a stack (see `phcalc-cli filter`)
+    #[arg(long = "filter")]
+    filters: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct Optimal {
+    focal_length_mm: f32,
+    magnification: f32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    filters: Option<String>,
+    wavelength_nm: f32,
+    diameter_mm: f32,
+    fnumber: f32,
+}
+
+impl fmt::Display for Optimal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(filters) = &self.filters {
+            writeln!(
+                f,
+                "Through {filters} the light centres at {:.0} nm",
+                self.wavelength_nm
+            )?;
+        }
+        writeln!(
+            f,
+            "Optimal pinhole Ø for {:.0}mm is {:.2} mm (at {:.1} magnification)",
+            self.focal_length_mm, self.diameter_mm, self.magnification
+        )?;
+        write!(f, "F-stop is f/{:.1}", self.fnumber)
+    }
+}
+
+pub fn optimal(args: &OptimalArgs) -> Result<Optimal, String> {
+    let stack = pinhole::filters::stack(&args.filters)?;
+    let wavelength = stack.wavelength(args.wavelength);
+    let magnification = args.focal / (args.distance * 1000.);
+    let diameter = pinhole::calc_optimalsize(args.focal, wavelength, args.rayleigh, magnification);
+    Ok(Optimal {
+        focal_length_mm: args.focal,
+        magnification,
+        filters: (!stack.is_empty()).then(|| stack.to_string()),
+        wavelength_nm: wavelength,
+        diameter_mm: diameter,
+        fnumber: pinhole::fnumber(args.focal, diameter),
+    })
+}
+
+#[derive(Args, Debug)]
+pub struct AngleArgs {
+    /// Pinhole diameter, e.g. 0.3mm
+    #[arg(long, value_parser = units::length)]
+    diameter: f32,
+    /// Pinhole plate thickness, e.g. 0.04mm
+    #[arg(long, value_parser = units::length)]
+    thickness: f32,
+}
+
+#[derive(Serialize)]
+pub struct Angle {
+    half_angle_deg: f32,
+    view_angle_deg: f32,
+}
+
+impl fmt::Display for Angle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "View angle is {:.1}˚ ({:.1}˚ from the axis)",
+            self.view_angle_deg, self.half_angle_deg
+        )
+    }
+}
+
+pub fn angle(args: &AngleArgs) -> Angle {
+    let half = pinhole::calc_viewangle(args.diameter, args.thickness);
+    Angle {
+        half_angle_deg: half,
+        view_angle_deg: 2. * half,
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct VignettingArgs {
+    /// Focal length, e.g. 50mm
+    #[arg(long, value_parser = units::length)]
+    focal: f32,
+    #[command(flatten)]
+    target: Target,
+}
+
+#[derive(Serialize)]
+pub struct Vignetting {
+    projection_diameter_mm: f32,
+    falloff: f32,
+    stops: f32,
+    angle_deg: f32,
+}
+
+impl fmt::Display for Vignetting {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Vignetting for a {:.0}mm projection Ø is {:.1} f-stops ({:.2}) at a {:.1}˚ angle",
+            self.projection_diameter_mm, self.stops, self.falloff, self.angle_deg
+        )
+    }
+}
+
+pub fn vignetting(args: &VignettingArgs) -> Result<Vignetting, String> {
+    let projection = args.target.diameter()?;
+    let (falloff, angle) = pinhole::calc_vignetting(args.focal, projection / 2.);
+    Ok(Vignetting {
+        projection_diameter_mm: projection,
+        falloff,
+        stops: pinhole::stop_equivalent(falloff),
+        angle_deg: angle,
+    })
+}
+
+#[derive(Args, Debug)]
+pub struct CoverageArgs {
+    /// Focal length, e.g. 50mm
+    #[arg(long, value_parser = units::length)]
+    focal: f32,
+    /// Pinhole diameter, e.g. 0.3mm
+    #[arg(long, value_parser = units::length)]
+    diameter: f32,
+    /// Pinhole plate thickness, e.g. 0.04mm
+    #[arg(long, value_parser = units::length)]
+    thickness: f32,
+    /// Film format to check, e.g. 6x9
+    #[arg(long, conflicts_with = "projection")]
+    format: Option<String>,
+    /// Projection diameter to check, e.g. 108mm
+    #[arg(long, value_parser = units::length)]
+    projection: Option<f32>,
+}
+
+#[derive(Serialize)]
+pub struct Coverage {
+    view_angle_deg: f32,
+    coverage_diameter_mm: f32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    projection_diameter_mm: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    needed_focal_length_mm: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    covered: Option<bool>,
+}
+
+impl fmt::Display for Coverage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "View angle is {:.1}˚ which covers a diameter of {:.1}mm",
+            self.view_angle_deg, self.coverage_diameter_mm
+        )?;
+        if let (Some(projection), Some(needed), Some(covered)) = (
+            self.projection_diameter_mm,
+            self.needed_focal_length_mm,
+            self.covered,
+        ) {
+            writeln!(f)?;
+            write!(
+                f,
+                "A {:.0}mm projection Ø {} covered, it needs a focal length of at least {:.1}mm",
+                projection,
+                if covered { "is" } else { "is not" },
+                needed
+            )?;
+        }
+        Ok(())
+    }
+}
+
+pub fn coverage(args: &CoverageArgs) -> Result<Coverage, String> {
+    let angle = pinhole::calc_viewangle(args.diameter, args.thickness);
+    let radius = pinhole::coverage_radius(args.focal, angle);
+    let projection = match (&args.format, args.projection) {
+        (Some(name), _) => Some(
+            pinhole::formats::find(name)
+                .ok_or_else(|| format!("unknown format '{name}'"))?
+                .projection_diameter(),
+        ),
+        (None, projection) => projection,
+    };
+    let needed = projection.map(|p| pinhole::needed_focallength(p / 2., angle));
+    Ok(Coverage {
+        view_angle_deg: 2. * angle,
+        coverage_diameter_mm: 2. * radius,
+        projection_diameter_mm: projection,
+        needed_focal_length_mm: needed,
+        covered: projection.map(|p| 2. * radius >= p),
+    })
+}
+
+#[derive(Args, Debug)]
+pub struct ExposureArgs {
+    /// Focal length, e.g. 50mm
+    #[arg(long, value_parser = units::length)]
+    focal: f32,
+    /// Pinhole diameter, e.g. 0.3mm
+    #[arg(long, value_parser = units::length)]
+    diameter: f32,
+    /// F-stop the meter reading is for
+    #[arg(long, default_value = "f/32", value_parser = units::fstop)]
+    at: f32,
+    /// Metered exposure time, e.g. 1/125s
+    #[arg(long, value_parser = units::time)]
+    metered: Option<f32>,
+}
+
+#[derive(Serialize)]
+pub struct Exposure {
+    fnumber: f32,
+    marked: String,
+    reference_fnumber: f32,
+    stops: f32,
+    factor: f32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    metered_s: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    exposure_s: Option<f32>,
+}
+
+impl fmt::Display for Exposure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "F-stop is f/{:.1} ({}), {} stops from f/{}: exposure × {:.1}",
+            self.fnumber,
+            self.marked,
+            stops::thirds(self.stops),
+            self.reference_fnumber,
+            self.factor
+        )?;
+        if let (Some(metered), Some(exposure)) = (self.metered_s, self.exposure_s) {
+            writeln!(f)?;
+            write!(f, "Metered {metered}s becomes {exposure:.1}s")?;
+        }
+        Ok(())
+    }
+}
+
+pub fn exposure(args: &ExposureArgs) -> Exposure {
+    let n = pinhole::fnumber(args.focal, args.diameter);
+    let stops = stops::between_fnumbers(args.at, n);
+    Exposure {
+        fnumber: n,
+        marked: stops::offset(n, stops::Division::Third).to_string(),
+        reference_fnumber: args.at,
+        stops,
+        factor: stops::time(1., stops),
+        metered_s: args.metered,
+        exposure_s: args.metered.map(|t| stops::time(t, stops)),
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct FormatArgs {
+    /// Format to show; lists all formats when left out
+    name: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct Format {
+    name: &'static str,
+    width_mm: f32,
+    height_mm: f32,
+    projection_diameter_mm: f32,
+}
+
+impl From<&pinhole::formats::Format> for Format {
+    fn from(f: &pinhole::formats::Format) -> Self {
+        Format {
+            name: f.name,
+            width_mm: f.width,
+            height_mm: f.height,
+            projection_diameter_mm: f.projection_diameter(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(transparent)]
+pub struct Formats(Vec<Format>);
+
+impl fmt::Display for Formats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, format) in self.0.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(
+                f,
+                "{:<8} {:>6.1} x {:>6.1} mm, projection Ø {:.0}mm",
+                format.name, format.width_mm, format.height_mm, format.projection_diameter_mm
+            )?;
+        }
+        Ok(())
+    }
+}
+
+pub fn format(args: &FormatArgs) -> Result<Formats, String> {
+    match &args.name {
+        Some(name) => pinhole::formats::find(name)
+            .map(|f| Formats(vec![f.into()]))
+            .ok_or_else(|| format!("unknown format '{name}'")),
+        None => Ok(Formats(
+            pinhole::formats::FORMATS.iter().map(Format::from).collect(),
+        )),
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct FilterArgs {
+    /// Filters to stack, e.g. "Red 25" ND8; lists all filters when left out
+    names: Vec<String>,
+    /// Wavelength the pinhole is sized for without filters, e.g. 550nm
+    #[arg(long, default_value = "550nm", value_parser = units::wavelength)]
+    wavelength: f32,
+}
+
+#[derive(Serialize)]
+pub struct Filter {
+    name: &'static str,
+    factor: f32,
+    stops: f32,
+    spectral: bool,
+}
+
+impl From<&pinhole::filters::Filter> for Filter {
+    fn from(f: &pinhole::filters::Filter) -> Self {
+        Filter {
+            name: f.name,
+            factor: f.factor,
+            stops: f.stops(),
+            spectral: f.curve.is_some(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct StackTotal {
+    factor: f32,
+    stops: f32,
+    wavelength_nm: f32,
+}
+
+#[derive(Serialize)]
+pub struct Filters {
+    filters: Vec<Filter>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stack: Option<StackTotal>,
+}
+
+impl fmt::Display for Filters {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, filter) in self.filters.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(
+                f,
+                "{:<12} ×{:<6} {:>5.2} stops{}",
+                filter.name,
+                filter.factor,
+                filter.stops,
+                if filter.spectral { ", coloured" } else { "" }
+            )?;
+        }
+        if let Some(stack) = &self.stack {
+            writeln!(f)?;
+            write!(
+                f,
+                "Together exposure × {:.0}, {} stops, the light centres at {:.0} nm",
+                stack.factor,
+                stops::thirds(stack.stops),
+                stack.wavelength_nm
+            )?;
+        }
+        Ok(())
+    }
+}
+
+pub fn filter(args: &FilterArgs) -> Result<Filters, String> {
+    if args.names.is_empty() {
+        return Ok(Filters {
+            filters: pinhole::filters::FILTERS.iter().map(Filter::from).collect(),
+            stack: None,
+        });
+    }
+    let stack = pinhole::filters::stack(&args.names)?;
+    Ok(Filters {
+        filters: stack.filters.iter().map(|f| Filter::from(*f)).collect(),
+        stack: Some(StackTotal {
+            factor: stack.factor(),
+            stops: stack.stops(),
+            wavelength_nm: stack.wavelength(args.wavelength),
+        }),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn six_by_nine() -> Target {
+        Target {
+            format: Some("6x9".to_string()),
+            projection: None,
+        }
+    }
+
+    #[test]
+    fn optimal_at_infinity() {
+        let o = optimal(&OptimalArgs {
+            focal: 50.,
+            wavelength: 550.,
+            rayleigh: 1.56,
+            distance: f32::INFINITY,
+            filters: Vec::new(),
+        })
+        .unwrap();
+        assert_eq!(
+            o.to_string(),
+            "Optimal pinhole Ø for 50mm is 0.26 mm (at 0.0 magnification)\nF-stop is f/193.3"
+        );
+    }
+
+    #[test]
+    fn angles() {
+        let a = angle(&AngleArgs {
+            diameter: 0.3,
+            thickness: 0.1,
+        });
+        assert_eq!(a.to_string(), "View angle is 143.1˚ (71.6˚ from the axis)");
+        assert_eq!(a.view_angle_deg, 2. * a.half_angle_deg);
+    }
+
+    #[test]
+    fn vignetting_for_a_format() {
+        let v = vignetting(&VignettingArgs {
+            focal: 50.,
+            target: six_by_nine(),
+        })
+        .unwrap();
+        assert_eq!(
+            v.to_string(),
+            "Vignetting for a 109mm projection Ø is 2.3 f-stops (0.21) at a 47.5˚ angle"
+        );
+        let unknown = Target {
+            format: Some("nope".to_string()),
+            projection: None,
+        };
+        assert!(
+            vignetting(&VignettingArgs {
+                focal: 50.,
+                target: unknown,
+            })
+            .is_err_and(|e| e == "unknown format 'nope'")
+        );
+    }
+
+    #[test]
+    fn coverage_of_a_format() {
+        let mut args = CoverageArgs {
+            focal: 50.,
+            diameter: 0.3,
+            thickness: 0.1,
+            format: Some("6x9".to_string()),
+            projection: None,
+        };
+        let c = coverage(&args).unwrap();
+        assert_eq!(c.covered, Some(true));
+        assert_eq!(
+            c.to_string(),
+            "View angle is 143.1˚ which covers a diameter of 300.0mm\n\
+             A 109mm projection Ø is covered, it needs a focal length of at least 18.2mm"
+        );
+        args.format = None;
+        args.projection = Some(400.);
+        assert_eq!(coverage(&args).unwrap().covered, Some(false));
+        args.projection = None;
+        assert_eq!(coverage(&args).unwrap().covered, None);
+    }
+
+    #[test]
+    fn exposure_from_a_reading() {
+        let mut args = ExposureArgs {
+            focal: 45.,
+            diameter: 0.25,
+            at: 32.,
+            metered: Some(1. / 125.),
+        };
+        assert_eq!(
+            exposure(&args).to_string(),
+            "F-stop is f/180.0 (f/180), 5 stops from f/32: exposure × 31.6\n\
+             Metered 0.008s becomes 0.3s"
+        );
+        args.metered = None;
+        assert_eq!(exposure(&args).exposure_s, None);
+    }
+
+    #[test]
+    fn formats() {
+        let one = format(&FormatArgs {
+            name: Some("4x5".to_string()),
+        })
+        .unwrap();
+        assert_eq!(
+            one.to_string(),
+            "4\"x5\"     101.6 x  127.0 mm, projection Ø 163mm"
+        );
+        let all = format(&FormatArgs { name: None }).unwrap();
+        assert_eq!(all.0.len(), pinhole::formats::FORMATS.len());
+        assert!(
+            format(&FormatArgs {
+                name: Some("nope".to_string()),
+            })
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn optimal_through_filters() {
+        let mut args = OptimalArgs {
+            focal: 50.,
+            wavelength: 550.,
+            rayleigh: 1.56,
+            distance: f32::INFINITY,
+            filters: Vec::new(),
+        };
+        let open = optimal(&args).unwrap();
+        assert_eq!(open.filters, None);
+        assert_eq!(open.wavelength_nm, 550.);
+        args.filters = vec!["red 25".to_string()];
+        let red = optimal(&args).unwrap();
+        assert_eq!(red.filters.as_deref(), Some("Red 25"));
+        assert!(red.wavelength_nm > 600.);
+        assert!(red.diameter_mm > open.diameter_mm);
+        args.filters = vec!["ND8".to_string(), "Polariser".to_string()];
+        assert!(optimal(&args).is_err_and(|e| e.starts_with("unknown filter 'Polariser'")));
+    }
+
+    #[test]
+    fn filter_list_and_stack() {
+        let all = filter(&FilterArgs {
+            names: Vec::new(),
+            wavelength: 550.,
+        })
+        .unwrap();
+        assert_eq!(all.filters.len(), pinhole::filters::FILTERS.len());
+        assert!(all.stack.is_none());
+        let stack = filter(&FilterArgs {
+            names: vec!["Red 25".to_string(), "ND8".to_string()],
+            wavelength: 550.,
+        })
+        .unwrap();
+        let total = stack.stack.as_ref().unwrap();
+        assert_eq!((total.factor, total.stops), (64., 6.));
+        assert!(
+            stack
+                .to_string()
+                .ends_with("Together exposure × 64, 6 stops, the light centres at 652 nm")
+        );
+        assert!(
+            filter(&FilterArgs {
+                names: vec!["x".to_string()],
+                wavelength: 550.,
+            })
+            .is_err()
+        );
+    }
+}
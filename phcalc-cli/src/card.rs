@@ -0,0 +1,554 @@
+use std::fmt::{self, Write as _};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use clap::{Args, ValueEnum};
+use pinhole::design::Design;
+use pinhole::exposure;
+use pinhole::film::{self, Film};
+use pinhole::filters::Stack;
+use serde::Serialize;
+
+use crate::flatfield::load_design;
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum Size {
+    /// 85 x 55 mm
+    BusinessCard,
+    /// 105 x 70 mm, for the back of a 6x9 camera
+    #[value(name = "6x9-back")]
+    Back6x9,
+    /// 21 stickers of 63.5 x 38.1 mm on A4 (Avery L7160)
+    StickerSheet,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum Reference {
+    /// Light values at ISO 100
+    Ev,
+    /// Metered times at f/16
+    F16,
+    /// Metered times at f/22
+    F22,
+}
+
+impl Reference {
+    fn fnumber(self) -> Option<f32> {
+        match self {
+            Reference::Ev => None,
+            Reference::F16 => Some(16.),
+            Reference::F22 => Some(22.),
+        }
+    }
+
+    fn heading(self) -> &'static str {
+        match self {
+            Reference::Ev => "EV",
+            Reference::F16 => "f/16",
+            Reference::F22 => "f/22",
+        }
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct CardArgs {
+    /// Design file (.pinhole.toml or .json)
+    #[arg(long)]
+    design: PathBuf,
+    /// Film, default the one in the design, e.g. "HP5 Plus"
+    #[arg(long)]
+    film: Option<String>,
+    /// What the meter reading on the card is
+    #[arg(long, value_enum, default_value_t = Reference::Ev)]
+    reference: Reference,
+    #[arg(long, value_enum, default_value_t = Size::BusinessCard)]
+    size: Size,
+    /// File to write, .svg or .pdf
+    #[arg(long, short)]
+    out: PathBuf,
+}
+
+#[derive(Serialize)]
+pub struct Row {
+    reading: String,
+    metered_s: f32,
+    exposure_s: f32,
+}
+
+#[derive(Serialize)]
+pub struct Card {
+    path: PathBuf,
+    title: String,
+    info: String,
+    reference: &'static str,
+    rows: Vec<Row>,
+}
+
+impl fmt::Display for Card {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{}", self.title)?;
+        writeln!(f, "{}", self.info)?;
+        for row in &self.rows {
+            writeln!(
+                f,
+                "{:>6}  {}",
+                row.reading,
+                exposure::format_time(row.exposure_s)
+            )?;
+        }
+        write!(f, "Wrote {}", self.path.display())
+    }
+}
+
+// Shutter speeds a meter shows, in full stops.
+const SHUTTER: [(&str, f32); 14] = [
+    ("1/1000", 1. / 1024.),
+    ("1/500", 1. / 512.),
+    ("1/250", 1. / 256.),
+    ("1/125", 1. / 128.),
+    ("1/60", 1. / 64.),
+    ("1/30", 1. / 32.),
+    ("1/15", 1. / 16.),
+    ("1/8", 1. / 8.),
+    ("1/4", 1. / 4.),
+    ("1/2", 1. / 2.),
+    ("1", 1.),
+    ("2", 2.),
+    ("4", 4.),
+    ("8", 8.),
+];
+
+// Readings taken without the filters, the exposures through them.
+pub fn rows(reference: Reference, n: f32, film: &Film, stack: &Stack) -> Vec<Row> {
+    let row = |reading: String, e: exposure::Exposure| Row {
+        reading,
+        metered_s: e.metered,
+        exposure_s: e.corrected,
+    };
+    match reference.fnumber() {
+        None => (3..=16)
+            .rev()
+            .map(|ev| {
+                let ev = ev as f32;
+                row(ev.to_string(), exposure::for_ev(stack.ev(ev), n, film))
+            })
+            .collect(),
+        Some(at) => SHUTTER
+            .iter()
+            .map(|(label, t)| {
+                row(
+                    label.to_string(),
+                    exposure::at_pinhole(stack.time(*t), at, n, Some(film)),
+                )
+            })
+            .collect(),
+    }
+}
+
+// What goes on the card, in mm from the top left.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Mark {
+    Text {
+        at: [f32; 2], // baseline
+        size: f32,
+        bold: bool,
+        text: String,
+    },
+    Line {
+        from: [f32; 2],
+        to: [f32; 2],
+    },
+    Frame {
+        min: [f32; 2],
+        max: [f32; 2],
+    },
+}
+
+pub struct Page {
+    pub width: f32,
+    pub height: f32,
+    pub marks: Vec<Mark>,
+}
+
+// One card at `origin`, the table in two halves side by side.
+fn layout(card: &Card, origin: [f32; 2], [w, h]: [f32; 2]) -> Vec<Mark> {
+    let [x, y] = origin;
+    let margin = 0.07 * h;
+    let text = |at: [f32; 2], size: f32, bold: bool, text: &str| Mark::Text {
+        at: [x + at[0], y + at[1]],
+        size,
+        bold,
+        text: text.to_string(),
+    };
+    let title = 0.1 * h;
+    let info = 0.065 * h;
+    let mut marks = vec![
+        text([margin, margin + 0.8 * title], title, true, &card.title),
+        text(
+            [margin, margin + title + 1.1 * info],
+            info,
+            false,
+            &card.info,
+        ),
+    ];
+    let top = margin + title + 1.6 * info + 0.03 * h;
+    let half = card.rows.len().div_ceil(2);
+    let row_h = (h - margin - top) / (half + 1) as f32;
+    let size = 0.62 * row_h;
+    let block = (w - 2. * margin) / 2.;
+    for b in 0..2 {
+        let left = margin + b as f32 * block;
+        let time_x = left + 0.36 * block;
+        let baseline = |i: usize| top + (i as f32 + 0.75) * row_h;
+        marks.push(text([left, baseline(0)], size, true, card.reference));
+        marks.push(text([time_x, baseline(0)], size, true, "Pinhole"));
+        marks.push(Mark::Line {
+            from: [x + left, y + top + row_h],
+            to: [x + left + 0.9 * block, y + top + row_h],
+        });
+        for (i, row) in card.rows.iter().skip(b * half).take(half).enumerate() {
+            marks.push(text([left, baseline(i + 1)], size, false, &row.reading));
+            marks.push(text(
+                [time_x, baseline(i + 1)],
+                size,
+                true,
+                &exposure::format_time(row.exposure_s),
+            ));
+        }
+    }
+    marks
+}
+
+pub fn page(card: &Card, size: Size) -> Page {
+    match size {
+        Size::BusinessCard | Size::Back6x9 => {
+            let [width, height] = match size {
+                Size::BusinessCard => [85., 55.],
+                _ => [105., 70.],
+            };
+            let mut marks = vec![Mark::Frame {
+                min: [0., 0.],
+                max: [width, height],
+            }];
+            marks.extend(layout(card, [0., 0.], [width, height]));
+            Page {
+                width,
+                height,
+                marks,
+            }
+        }
+        Size::StickerSheet => {
+            let mut marks = Vec::new();
+            for row in 0..7 {
+                for col in 0..3 {
+                    let origin = [7.2 + 66. * col as f32, 15.15 + 38.1 * row as f32];
+                    marks.extend(layout(card, origin, [63.5, 38.1]));
+                }
+            }
+            Page {
+                width: 210.,
+                height: 297.,
+                marks,
+            }
+        }
+    }
+}
+
+pub fn to_svg(page: &Page) -> String {
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{0}mm" height="{1}mm" viewBox="0 0 {0} {1}">"#,
+        page.width, page.height
+    );
+    let _ = writeln!(
+        svg,
+        r#"<rect width="{}" height="{}" fill="white"/>"#,
+        page.width, page.height
+    );
+    for mark in &page.marks {
+        let _ = match mark {
+            Mark::Text {
+                at,
+                size,
+                bold,
+                text,
+            } => writeln!(
+                svg,
+                r#"<text x="{:.2}" y="{:.2}" font-family="Helvetica, Arial, sans-serif" font-size="{:.2}"{}>{}</text>"#,
+                at[0],
+                at[1],
+                size,
+                if *bold { r#" font-weight="bold""# } else { "" },
+                escape(text)
+            ),
+            Mark::Line { from, to } => writeln!(
+                svg,
+                r#"<line x1="{:.2}" y1="{:.2}" x2="{:.2}" y2="{:.2}" stroke="black" stroke-width="0.2"/>"#,
+                from[0], from[1], to[0], to[1]
+            ),
+            Mark::Frame { min, max } => writeln!(
+                svg,
+                r##"<rect x="{:.2}" y="{:.2}" width="{:.2}" height="{:.2}" fill="none" stroke="#999" stroke-width="0.2"/>"##,
+                min[0],
+                min[1],
+                max[0] - min[0],
+                max[1] - min[1]
+            ),
+        };
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+// A one page PDF with the standard Helvetica fonts, no embedding needed.
+pub fn to_pdf(page: &Page) -> Vec<u8> {
+    const PT: f32 = 72. / 25.4;
+    let y = |v: f32| (page.height - v) * PT;
+    let mut content = Vec::new();
+    for mark in &page.marks {
+        match mark {
+            Mark::Text {
+                at,
+                size,
+                bold,
+                text,
+            } => {
+                let font = if *bold { "F2" } else { "F1" };
+                content.extend(
+                    format!(
+                        "BT /{font} {:.2} Tf {:.2} {:.2} Td (",
+                        size * PT,
+                        at[0] * PT,
+                        y(at[1])
+                    )
+                    .bytes(),
+                );
+                content.extend(pdf_string(text));
+                content.extend(b") Tj ET\n");
+            }
+            Mark::Line { from, to } => content.extend(
+                format!(
+                    "0.57 w {:.2} {:.2} m {:.2} {:.2} l S\n",
+                    from[0] * PT,
+                    y(from[1]),
+                    to[0] * PT,
+                    y(to[1])
+                )
+                .bytes(),
+            ),
+            Mark::Frame { min, max } => content.extend(
+                format!(
+                    "0.6 G 0.57 w {:.2} {:.2} {:.2} {:.2} re S 0 G\n",
+                    min[0] * PT,
+                    y(max[1]),
+                    (max[0] - min[0]) * PT,
+                    (max[1] - min[1]) * PT
+                )
+                .bytes(),
+            ),
+        }
+    }
+    let font = |name: &str| {
+        format!("<< /Type /Font /Subtype /Type1 /BaseFont /{name} /Encoding /WinAnsiEncoding >>")
+            .into_bytes()
+    };
+    let mut stream = format!("<< /Length {} >>\nstream\n", content.len()).into_bytes();
+    stream.extend(&content);
+    stream.extend(b"endstream");
+    let objects = [
+        b"<< /Type /Catalog /Pages 2 0 R >>".to_vec(),
+        b"<< /Type /Pages /Kids [3 0 R] /Count 1 >>".to_vec(),
+        format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {:.2} {:.2}] \
+             /Resources << /Font << /F1 4 0 R /F2 5 0 R >> >> /Contents 6 0 R >>",
+            page.width * PT,
+            page.height * PT
+        )
+        .into_bytes(),
+        font("Helvetica"),
+        font("Helvetica-Bold"),
+        stream,
+    ];
+    let mut pdf = b"%PDF-1.4\n".to_vec();
+    let mut offsets = Vec::new();
+    for (i, object) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        pdf.extend(format!("{} 0 obj\n", i + 1).bytes());
+        pdf.extend(object);
+        pdf.extend(b"\nendobj\n");
+    }
+    let xref = pdf.len();
+    pdf.extend(format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).bytes());
+    for offset in offsets {
+        pdf.extend(format!("{offset:010} 00000 n \n").bytes());
+    }
+    pdf.extend(
+        format!(
+            "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{xref}\n%%EOF\n",
+            objects.len() + 1
+        )
+        .bytes(),
+    );
+    pdf
+}
+
+// In WinAnsi, which has Latin-1 from 0xA0 up, and escaped for a PDF string.
+fn pdf_string(s: &str) -> Vec<u8> {
+    let mut out = Vec::new();
+    for c in s.chars() {
+        match c {
+            '(' | ')' | '\\' => out.extend([b'\\', c as u8]),
+            ' '..='~' | '\u{a0}'..='\u{ff}' => out.push(c as u32 as u8),
+            '˚' => out.push(0xb0),
+            _ => out.push(b'?'),
+        }
+    }
+    out
+}
+
+pub fn film_for(design: &Design, name: Option<&str>) -> Result<&'static Film, String> {
+    let name = name.unwrap_or(&design.film);
+    if name.is_empty() {
+        return Err("the design has no film, give --film".to_string());
+    }
+    film::find(name).ok_or_else(|| {
+        let names: Vec<_> = film::FILMS.iter().map(|f| f.name).collect();
+        format!("unknown film '{name}', one of: {}", names.join(", "))
+    })
+}
+
+fn is_pdf(path: &Path) -> Result<bool, String> {
+    match path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(str::to_lowercase)
+    {
+        Some(ext) if ext == "pdf" => Ok(true),
+        Some(ext) if ext == "svg" => Ok(false),
+        _ => Err(format!(
+            "cannot tell the file type of {}, use .svg or .pdf",
+            path.display()
+        )),
+    }
+}
+
+pub fn run(args: &CardArgs, json: bool) -> Result<ExitCode, String> {
+    let design = load_design(&args.design)?;
+    let film = film_for(&design, args.film.as_deref())?;
+    let stack = design.stack()?;
+    let pdf = is_pdf(&args.out)?;
+    let p = design.point();
+    let n = p.fnumber();
+    let mut info = format!(
+        "f/{n:.0} · {:.0}° · {} ISO {}",
+        2. * p.vignetting().1,
+        film.name,
+        film.iso
+    );
+    if !stack.is_empty() {
+        let _ = write!(info, " · {stack}");
+    }
+    let card = Card {
+        path: args.out.clone(),
+        title: if design.name.is_empty() {
+            "Pinhole".to_string()
+        } else {
+            design.name.clone()
+        },
+        info,
+        reference: args.reference.heading(),
+        rows: rows(args.reference, n, film, &stack),
+    };
+    let page = page(&card, args.size);
+    let bytes = if pdf {
+        to_pdf(&page)
+    } else {
+        to_svg(&page).into_bytes()
+    };
+    if let Err(e) = std::fs::write(&args.out, bytes) {
+        eprintln!("error: cannot write {}: {e}", args.out.display());
+        return Ok(ExitCode::FAILURE);
+    }
+    Ok(crate::emit(&card, json))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn card() -> Card {
+        let film = film::find("HP5 Plus").unwrap();
+        Card {
+            path: PathBuf::from("card.pdf"),
+            title: "Zero (2000)".to_string(),
+            info: "f/139 · 82° · Ilford HP5 Plus ISO 400".to_string(),
+            reference: "EV",
+            rows: rows(Reference::Ev, 138.9, film, &Stack::default()),
+        }
+    }
+
+    #[test]
+    fn rows_get_longer() {
+        let c = card();
+        assert_eq!(c.rows.len(), 14);
+        assert_eq!(c.rows[0].reading, "16");
+        assert!(c.rows.windows(2).all(|w| w[1].exposure_s > w[0].exposure_s));
+        let film = film::find("HP5 Plus").unwrap();
+        let f16 = rows(Reference::F16, 138.9, film, &Stack::default());
+        assert_eq!(f16[3].reading, "1/125");
+        assert!((f16[3].metered_s - (138.9f32 / 16.).powi(2) / 128.).abs() < 1e-4);
+        // Three stops of filter move the table three rows.
+        let nd8 = pinhole::filters::stack(&["ND8"]).unwrap();
+        let filtered = rows(Reference::Ev, 138.9, film, &nd8);
+        assert!((filtered[0].exposure_s / c.rows[3].exposure_s - 1.).abs() < 1e-4);
+    }
+
+    #[test]
+    fn sticker_sheet_has_21_cards() {
+        let c = card();
+        let one = page(&c, Size::BusinessCard).marks.len() - 1;
+        let sheet = page(&c, Size::StickerSheet);
+        assert_eq!(sheet.marks.len(), 21 * one);
+        // All inside the page.
+        for m in &sheet.marks {
+            if let Mark::Text { at, .. } = m {
+                assert!(at[0] < 210. && at[1] < 297.);
+            }
+        }
+    }
+
+    fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+        haystack.windows(needle.len()).position(|w| w == needle)
+    }
+
+    #[test]
+    fn pdf_structure() {
+        let pdf = to_pdf(&page(&card(), Size::BusinessCard));
+        assert!(pdf.starts_with(b"%PDF-1.4"));
+        assert!(pdf.ends_with(b"%%EOF\n"));
+        // Brackets escaped, the degree sign in WinAnsi.
+        assert!(find(&pdf, b"(Zero \\(2000\\)) Tj").is_some());
+        assert!(find(&pdf, b"82\xb0").is_some());
+        // The cross reference points at the objects.
+        let start = find(&pdf, b"startxref\n").unwrap() + 10;
+        let xref: usize = String::from_utf8_lossy(&pdf[start..])
+            .lines()
+            .next()
+            .and_then(|s| s.parse().ok())
+            .unwrap();
+        assert!(pdf[xref..].starts_with(b"xref"));
+        let last: usize = String::from_utf8_lossy(&pdf[xref..])
+            .lines()
+            .nth(8)
+            .and_then(|l| l[..10].parse().ok())
+            .unwrap();
+        assert!(pdf[last..].starts_with(b"6 0 obj"));
+    }
+}
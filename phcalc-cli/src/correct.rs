@@ -0,0 +1,307 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use clap::{Args, ValueEnum};
+use image::{ColorType, DynamicImage, Rgba32FImage};
+use pinhole::falloff::Map;
+use serde::Serialize;
+
+use crate::flatfield::{format, load_design};
+use crate::units;
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum Polarity {
+    /// Slides, prints and inverted scans: brighter is more light
+    Positive,
+    /// Scanned negatives: brighter is less light
+    Negative,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum Fit {
+    /// Cut off what comes out brighter than white
+    Clamp,
+    /// Darken the whole image so nothing clips
+    Scale,
+}
+
+#[derive(Args, Debug)]
+pub struct CorrectArgs {
+    /// Scans to correct, TIFF, PNG or JPEG
+    #[arg(required = true)]
+    scans: Vec<PathBuf>,
+    /// Design file (.pinhole.toml or .json)
+    #[arg(long)]
+    design: PathBuf,
+    /// Film format, default the one in the design
+    #[arg(long)]
+    format: Option<String>,
+    /// Pixel size on the film (default: the format's long side over the scan's)
+    #[arg(long, value_parser = units::length)]
+    pixel: Option<f32>,
+    /// Optical centre in scan pixels from the top left, e.g. 1810,1190 (default: the middle)
+    #[arg(long, value_name = "X,Y", value_parser = point)]
+    centre: Option<[f32; 2]>,
+    #[arg(long, value_enum, default_value_t = Polarity::Positive)]
+    polarity: Polarity,
+    /// Contrast of the negative, for --polarity negative
+    #[arg(long, default_value = "0.6", value_parser = units::factor)]
+    gamma: f32,
+    #[arg(long, value_enum, default_value_t = Fit::Clamp)]
+    fit: Fit,
+    /// The samples are linear, not sRGB encoded
+    #[arg(long)]
+    linear: bool,
+    /// Directory for the corrected scans, same file names
+    #[arg(long)]
+    out_dir: PathBuf,
+}
+
+#[derive(Serialize)]
+pub struct Corrected {
+    input: PathBuf,
+    output: PathBuf,
+    // What the corrected values were multiplied by to fit, 1 when clamped.
+    scale: f32,
+}
+
+#[derive(Serialize)]
+pub struct Correction {
+    files: Vec<Corrected>,
+}
+
+impl fmt::Display for Correction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, c) in self.files.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{} -> {}", c.input.display(), c.output.display())?;
+            if c.scale < 1. {
+                write!(f, " (scaled by {:.2})", c.scale)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+// "x,y" in pixels.
+fn point(s: &str) -> Result<[f32; 2], String> {
+    let parse = |v: &str| v.trim().parse::<f32>().map_err(|e| format!("'{v}': {e}"));
+    match s.split_once(',') {
+        Some((x, y)) => Ok([parse(x)?, parse(y)?]),
+        None => Err(format!("expected x,y in pixels, not '{s}'")),
+    }
+}
+
+pub fn srgb_to_linear(v: f32) -> f32 {
+    if v <= 0.04045 {
+        v / 12.92
+    } else {
+        ((v + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+pub fn linear_to_srgb(v: f32) -> f32 {
+    if v <= 0.0031308 {
+        v * 12.92
+    } else {
+        1.055 * v.powf(1. / 2.4) - 0.055
+    }
+}
+
+// A linear sample with the falloff taken out. On a negative less light means
+// less density, so the scan is too bright where the illumination was low.
+pub fn correct(v: f32, illumination: f32, polarity: Polarity, gamma: f32) -> f32 {
+    let illumination = illumination.max(1e-4);
+    match polarity {
+        Polarity::Positive => v / illumination,
+        Polarity::Negative => v * illumination.powf(gamma),
+    }
+}
+
+// Corrects the image in place and returns the scale it got to fit.
+pub fn apply(img: &mut Rgba32FImage, map: &Map, args: &CorrectArgs) -> f32 {
+    let decode = |v: f32| if args.linear { v } else { srgb_to_linear(v) };
+    let encode = |v: f32| if args.linear { v } else { linear_to_srgb(v) };
+    let mut brightest = 0f32;
+    for (x, y, px) in img.enumerate_pixels_mut() {
+        let illumination = map.get(x as usize, y as usize);
+        for v in &mut px.0[..3] {
+            *v = correct(decode(*v), illumination, args.polarity, args.gamma);
+            brightest = brightest.max(*v);
+        }
+    }
+    let scale = match args.fit {
+        Fit::Scale if brightest > 1. => 1. / brightest,
+        _ => 1.,
+    };
+    for px in img.pixels_mut() {
+        for v in &mut px.0[..3] {
+            *v = encode((*v * scale).clamp(0., 1.));
+        }
+    }
+    scale
+}
+
+// The falloff over a scan of w × h pixels, around --centre or the middle.
+fn map_for(p: &pinhole::sweep::Point, w: u32, h: u32, pixel: f32, args: &CorrectArgs) -> Map {
+    let centre = args.centre.unwrap_or([w as f32 / 2., h as f32 / 2.]);
+    pinhole::falloff::grid(p, w as usize, h as usize, pixel, centre)
+}
+
+// Back to the colour type and depth of the scan.
+fn convert(img: Rgba32FImage, color: ColorType) -> DynamicImage {
+    let img = DynamicImage::ImageRgba32F(img);
+    match color {
+        ColorType::L8 => img.to_luma8().into(),
+        ColorType::La8 => img.to_luma_alpha8().into(),
+        ColorType::Rgb8 => img.to_rgb8().into(),
+        ColorType::Rgba8 => img.to_rgba8().into(),
+        ColorType::L16 => img.to_luma16().into(),
+        ColorType::La16 => img.to_luma_alpha16().into(),
+        ColorType::Rgb16 => img.to_rgb16().into(),
+        ColorType::Rgba16 => img.to_rgba16().into(),
+        ColorType::Rgb32F => img.to_rgb32f().into(),
+        _ => img,
+    }
+}
+
+fn correct_file(
+    path: &Path,
+    args: &CorrectArgs,
+    p: &pinhole::sweep::Point,
+    long_side: f32,
+) -> Result<Corrected, String> {
+    let output = args.out_dir.join(path.file_name().ok_or("not a file")?);
+    if output.canonicalize().ok() == Some(path.canonicalize().map_err(|e| e.to_string())?) {
+        return Err("will not overwrite the scan, choose another --out-dir".to_string());
+    }
+    let scan = image::open(path).map_err(|e| e.to_string())?;
+    let color = scan.color();
+    let mut img = scan.into_rgba32f();
+    let (w, h) = img.dimensions();
+    let pixel = args.pixel.unwrap_or(long_side / w.max(h) as f32);
+    let map = map_for(p, w, h, pixel, args);
+    let scale = apply(&mut img, &map, args);
+    convert(img, color)
+        .save(&output)
+        .map_err(|e| e.to_string())?;
+    Ok(Corrected {
+        input: path.to_path_buf(),
+        output,
+        scale,
+    })
+}
+
+pub fn run(args: &CorrectArgs, json: bool) -> Result<ExitCode, String> {
+    let design = load_design(&args.design)?;
+    let format = format(&design, args.format.as_deref())?;
+    let long_side = 2. * format.radii()[1];
+    let p = design.point();
+    if let Err(e) = std::fs::create_dir_all(&args.out_dir) {
+        eprintln!("error: cannot create {}: {e}", args.out_dir.display());
+        return Ok(ExitCode::FAILURE);
+    }
+    let mut files = Vec::new();
+    let mut failed = false;
+    // A scan that cannot be read or written does not stop the batch.
+    for path in &args.scans {
+        match correct_file(path, args, &p, long_side) {
+            Ok(c) => files.push(c),
+            Err(e) => {
+                eprintln!("error: {}: {e}", path.display());
+                failed = true;
+            }
+        }
+    }
+    let code = crate::emit(&Correction { files }, json);
+    Ok(if failed { ExitCode::FAILURE } else { code })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    #[derive(Parser)]
+    struct Cli {
+        #[command(flatten)]
+        args: CorrectArgs,
+    }
+
+    fn args(extra: &[&str]) -> CorrectArgs {
+        let base = [
+            "correct",
+            "scan.tif",
+            "--design",
+            "d.toml",
+            "--out-dir",
+            "out",
+        ];
+        Cli::parse_from(base.iter().chain(extra)).args
+    }
+
+    #[test]
+    fn srgb_roundtrip() {
+        for v in [0., 0.002, 0.2, 0.5, 1.] {
+            assert!((linear_to_srgb(srgb_to_linear(v)) - v).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn negative_corners_get_darker() {
+        assert_eq!(correct(0.5, 0.5, Polarity::Positive, 0.6), 1.);
+        assert!(correct(0.5, 0.5, Polarity::Negative, 0.6) < 0.5);
+    }
+
+    #[test]
+    fn flat_positive_comes_back_flat() {
+        let p = pinhole::sweep::Point::default();
+        let map = pinhole::falloff::grid(&p, 8, 6, 5., [4., 3.]);
+        let mut img = Rgba32FImage::from_fn(8, 6, |x, y| {
+            let v = 0.5 * map.get(x as usize, y as usize);
+            image::Rgba([v, v, v, 1.])
+        });
+        let scale = apply(&mut img, &map, &args(&["--linear"]));
+        assert_eq!(scale, 1.);
+        assert!(
+            img.pixels()
+                .all(|px| (px.0[0] - 0.5).abs() < 1e-5 && px.0[3] == 1.)
+        );
+    }
+
+    #[test]
+    fn scale_keeps_highlights() {
+        let p = pinhole::sweep::Point::default();
+        let map = pinhole::falloff::grid(&p, 8, 6, 5., [4., 3.]);
+        let mut img = Rgba32FImage::from_pixel(8, 6, image::Rgba([1., 1., 1., 1.]));
+        let scale = apply(&mut img, &map, &args(&["--linear", "--fit", "scale"]));
+        assert!(scale < 1.);
+        assert!(img.pixels().all(|px| px.0[0] <= 1.));
+    }
+
+    #[test]
+    fn off_centre_comes_back_flat() {
+        // A scan whose optical centre sits up and left of the middle.
+        let p = pinhole::sweep::Point::default();
+        let falloff = pinhole::falloff::grid(&p, 8, 6, 5., [2., 1.]);
+        let scan = Rgba32FImage::from_fn(8, 6, |x, y| {
+            let v = 0.5 * falloff.get(x as usize, y as usize);
+            image::Rgba([v, v, v, 1.])
+        });
+        let spread = |img: &Rgba32FImage| {
+            let values = img.pixels().map(|px| px.0[0]);
+            values.clone().fold(0f32, f32::max) - values.fold(1f32, f32::min)
+        };
+        let off = args(&["--linear", "--centre", "2,1"]);
+        let mut img = scan.clone();
+        apply(&mut img, &map_for(&p, 8, 6, 5., &off), &off);
+        assert!(spread(&img) < 1e-5, "{}", spread(&img));
+        let middle = args(&["--linear"]);
+        let mut img = scan;
+        apply(&mut img, &map_for(&p, 8, 6, 5., &middle), &middle);
+        assert!(spread(&img) > 0.01, "{}", spread(&img));
+    }
+}
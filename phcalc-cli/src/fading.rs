@@ -0,0 +1,153 @@
+use std::fmt;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::Args;
+use pinhole::exposure::{self, format_time};
+use pinhole::sun::{self, Date, Place};
+use serde::Serialize;
+
+use crate::card::film_for;
+use crate::flatfield::load_design;
+use crate::units;
+
+#[derive(Args, Debug)]
+pub struct FadingArgs {
+    /// Design file (.pinhole.toml or .json)
+    #[arg(long)]
+    design: PathBuf,
+    /// Film, default the one in the design, e.g. "HP5 Plus"
+    #[arg(long)]
+    film: Option<String>,
+    /// Light reading as time from the start and EV at ISO 100, e.g. 10min=8.5,
+    /// without the design's filters; give it more than once for the light as
+    /// it changes
+    #[arg(long = "reading", value_parser = units::reading, allow_hyphen_values = true)]
+    readings: Vec<(f32, f32)>,
+    /// Without readings, the clear-sky light at this latitude (north positive)
+    #[arg(long, allow_negative_numbers = true)]
+    latitude: Option<f64>,
+    /// and longitude (east positive)
+    #[arg(long, allow_negative_numbers = true)]
+    longitude: Option<f64>,
+    /// on this date, e.g. 2025-06-21
+    #[arg(long)]
+    date: Option<Date>,
+    /// Time of day the exposure starts, e.g. 21:30
+    #[arg(long, value_parser = units::clock)]
+    start: Option<f64>,
+    /// Local time zone in hours from UTC, e.g. 2 for CEST
+    #[arg(long, default_value_t = 0., allow_negative_numbers = true)]
+    utc_offset: f64,
+    /// Give up after this long
+    #[arg(long, default_value = "12h", value_parser = units::time)]
+    limit: f32,
+}
+
+#[derive(Serialize)]
+pub struct Fading {
+    film: String,
+    metered_s: f32,
+    steady_s: f32,
+    duration_s: f32,
+    start: Option<String>,
+    end: Option<String>,
+}
+
+impl fmt::Display for Fading {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "The light at the start, or once it comes up, asks for {} ({} metered) on {}",
+            format_time(self.steady_s),
+            format_time(self.metered_s),
+            self.film
+        )?;
+        write!(
+            f,
+            "As the light changes it takes {}",
+            format_time(self.duration_s)
+        )?;
+        if let (Some(start), Some(end)) = (&self.start, &self.end) {
+            write!(f, ", from {start} to {end}")?;
+        }
+        Ok(())
+    }
+}
+
+// "21:30:05", past midnight round to the next day.
+fn time_of_day(hours: f64) -> String {
+    let secs = (hours.rem_euclid(24.) * 3600.).round() as u32 % 86400;
+    format!("{:02}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+}
+
+pub fn run(args: &FadingArgs, json: bool) -> Result<ExitCode, String> {
+    let design = load_design(&args.design)?;
+    let film = film_for(&design, args.film.as_deref())?;
+    let stack = design.stack()?;
+    let n = design.point().fnumber();
+    let fading = if args.readings.is_empty() {
+        let (Some(latitude), Some(longitude), Some(date), Some(start)) =
+            (args.latitude, args.longitude, args.date, args.start)
+        else {
+            return Err(
+                "give --reading, or --latitude, --longitude, --date and --start for daylight"
+                    .to_string(),
+            );
+        };
+        let place = Place {
+            latitude,
+            longitude,
+        };
+        let light = sun::light_from(place, date, start - args.utc_offset);
+        exposure::through(
+            |t| light(t).map(|ev| stack.ev(ev)),
+            film.iso,
+            n,
+            Some(film),
+            args.limit,
+        )
+    } else {
+        let mut readings = args.readings.clone();
+        readings.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let light = exposure::from_points(&readings);
+        exposure::through(
+            |t| light(t).map(|ev| stack.ev(ev)),
+            film.iso,
+            n,
+            Some(film),
+            args.limit,
+        )
+    };
+    let Some(fading) = fading else {
+        eprintln!(
+            "error: not enough light to finish within {}",
+            format_time(args.limit)
+        );
+        return Ok(ExitCode::FAILURE);
+    };
+    Ok(crate::emit(
+        &Fading {
+            film: film.name.to_string(),
+            metered_s: fading.at_start.metered,
+            steady_s: fading.at_start.corrected,
+            duration_s: fading.duration,
+            start: args.start.map(time_of_day),
+            end: args
+                .start
+                .map(|start| time_of_day(start + fading.duration as f64 / 3600.)),
+        },
+        json,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clock_times() {
+        assert_eq!(time_of_day(21.5), "21:30:00");
+        assert_eq!(time_of_day(24.25 + 5. / 3600.), "00:15:05");
+    }
+}
@@ -0,0 +1,167 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use clap::Args;
+use image::{ImageBuffer, Luma, Rgb32FImage};
+use pinhole::design::Design;
+use pinhole::falloff::Map;
+use serde::Serialize;
+
+use crate::units;
+
+#[derive(Args, Debug)]
+pub struct FlatfieldArgs {
+    /// Design file (.pinhole.toml or .json)
+    #[arg(long)]
+    design: PathBuf,
+    /// Film format, default the one in the design
+    #[arg(long)]
+    format: Option<String>,
+    /// Pixel size on the film, e.g. 10.6um for a 2400 dpi scan
+    #[arg(long, default_value = "10um", value_parser = units::length)]
+    pixel: f32,
+    /// Write 32-bit float samples (TIFF, always for EXR) instead of 16-bit
+    #[arg(long)]
+    float: bool,
+    /// Image to write, .png, .tif or .exr
+    #[arg(long, short)]
+    out: PathBuf,
+}
+
+#[derive(Serialize)]
+pub struct Flatfield {
+    path: PathBuf,
+    width: usize,
+    height: usize,
+    corner: f32,
+    corner_stops: f32,
+}
+
+impl fmt::Display for Flatfield {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Wrote a {} x {} flat field to {}, the corners get {:.0}% ({:.1} stops less)",
+            self.width,
+            self.height,
+            self.path.display(),
+            100. * self.corner,
+            self.corner_stops
+        )
+    }
+}
+
+pub fn load_design(path: &Path) -> Result<Design, String> {
+    Design::load(path).map_err(|e| format!("cannot read design {}: {e}", path.display()))
+}
+
+// The catalogue format by name, or the one the design is for.
+pub fn format(
+    design: &Design,
+    name: Option<&str>,
+) -> Result<&'static pinhole::formats::Format, String> {
+    match name {
+        Some(name) => {
+            pinhole::formats::find(name).ok_or_else(|| format!("unknown format '{name}'"))
+        }
+        None => design
+            .point()
+            .format
+            .ok_or_else(|| "the design has no catalogue format, give --format".to_string()),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Depth {
+    Sixteen,
+    Float,
+}
+
+// From the file name, PNG only holds 16 bits and EXR only floats.
+pub fn depth(path: &Path, float: bool) -> Result<Depth, String> {
+    let ext = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(str::to_lowercase);
+    match (ext.as_deref(), float) {
+        (Some("png"), false) | (Some("tif" | "tiff"), false) => Ok(Depth::Sixteen),
+        (Some("tif" | "tiff" | "exr"), _) => Ok(Depth::Float),
+        (Some("png"), true) => Err("PNG has no float samples, use .tif or .exr".to_string()),
+        _ => Err(format!(
+            "cannot tell the image type of {}, use .png, .tif or .exr",
+            path.display()
+        )),
+    }
+}
+
+pub fn save(map: &Map, path: &Path, depth: Depth) -> Result<(), image::ImageError> {
+    let (w, h) = (map.width as u32, map.height as u32);
+    match depth {
+        Depth::Sixteen => {
+            let samples = map.values.iter().map(|v| to_u16(*v)).collect();
+            ImageBuffer::<Luma<u16>, Vec<u16>>::from_raw(w, h, samples)
+                .expect("one sample per pixel")
+                .save(path)
+        }
+        // The float writers take RGB only.
+        Depth::Float => {
+            let samples = map.values.iter().flat_map(|v| [*v; 3]).collect();
+            Rgb32FImage::from_raw(w, h, samples)
+                .expect("three samples per pixel")
+                .save(path)
+        }
+    }
+}
+
+pub fn to_u16(v: f32) -> u16 {
+    (v.clamp(0., 1.) * u16::MAX as f32).round() as u16
+}
+
+pub fn run(args: &FlatfieldArgs, json: bool) -> Result<ExitCode, String> {
+    let design = load_design(&args.design)?;
+    let format = format(&design, args.format.as_deref())?;
+    let depth = depth(&args.out, args.float)?;
+    let map = pinhole::falloff::map(&design.point(), format, args.pixel);
+    if let Err(e) = save(&map, &args.out, depth) {
+        eprintln!("error: cannot write {}: {e}", args.out.display());
+        return Ok(ExitCode::FAILURE);
+    }
+    let corner = map.min();
+    Ok(crate::emit(
+        &Flatfield {
+            path: args.out.clone(),
+            width: map.width,
+            height: map.height,
+            corner,
+            corner_stops: pinhole::stop_equivalent(corner),
+        },
+        json,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn depth_from_extension() {
+        assert_eq!(depth(Path::new("flat.PNG"), false), Ok(Depth::Sixteen));
+        assert_eq!(depth(Path::new("flat.tif"), true), Ok(Depth::Float));
+        assert_eq!(depth(Path::new("flat.exr"), false), Ok(Depth::Float));
+        assert!(depth(Path::new("flat.png"), true).is_err());
+        assert!(depth(Path::new("flat.jpg"), false).is_err());
+    }
+
+    #[test]
+    fn sixteen_bit_roundtrip() {
+        let p = pinhole::sweep::Point::default();
+        let map = pinhole::falloff::map(&p, pinhole::formats::find("35mm").unwrap(), 1.);
+        let path = std::env::temp_dir().join("phcalc-flatfield-test.png");
+        save(&map, &path, Depth::Sixteen).unwrap();
+        let back = image::open(&path).unwrap().into_luma16();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(back.dimensions(), (36, 24));
+        assert_eq!(back.get_pixel(0, 0).0[0], to_u16(map.get(0, 0)));
+    }
+}
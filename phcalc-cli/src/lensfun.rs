@@ -0,0 +1,102 @@
+use std::fmt;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::Args;
+use pinhole::design::Design;
+use pinhole::formats::Format;
+use serde::Serialize;
+
+use crate::flatfield::{format, load_design};
+
+#[derive(Args, Debug)]
+pub struct LensfunArgs {
+    /// Design file (.pinhole.toml or .json)
+    #[arg(long)]
+    design: PathBuf,
+    /// Film format or sensor, default the one in the design
+    #[arg(long)]
+    format: Option<String>,
+    /// Lens maker to file the profile under
+    #[arg(long, default_value = "Pinhole")]
+    maker: String,
+    /// Camera mount the pinhole sits on, as lensfun names it
+    #[arg(long, default_value = "Generic")]
+    mount: String,
+    /// Write the XML to a file (e.g. ~/.local/share/lensfun/pinhole.xml)
+    #[arg(long, short)]
+    out: Option<PathBuf>,
+}
+
+#[derive(Serialize)]
+pub struct Profile {
+    model: String,
+    focal_length_mm: f32,
+    fnumber: f32,
+    crop_factor: f32,
+    k1: f32,
+    k2: f32,
+    k3: f32,
+    #[serde(skip)]
+    xml: String,
+}
+
+impl fmt::Display for Profile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.xml.trim_end())
+    }
+}
+
+fn profile(design: &Design, format: &Format, maker: &str, mount: &str) -> Profile {
+    let p = design.point();
+    let v = pinhole::lensfun::fit(&p, format);
+    Profile {
+        model: pinhole::lensfun::model(design),
+        focal_length_mm: p.focal_length,
+        fnumber: p.fnumber(),
+        crop_factor: pinhole::lensfun::crop_factor(format),
+        k1: v.k1,
+        k2: v.k2,
+        k3: v.k3,
+        xml: pinhole::lensfun::to_xml(design, format, maker, mount),
+    }
+}
+
+pub fn run(args: &LensfunArgs, json: bool) -> Result<ExitCode, String> {
+    let design = load_design(&args.design)?;
+    let format = format(&design, args.format.as_deref())?;
+    let profile = profile(&design, format, &args.maker, &args.mount);
+    if let Some(path) = &args.out {
+        if let Err(e) = std::fs::write(path, &profile.xml) {
+            eprintln!("error: cannot write {}: {e}", path.display());
+            return Ok(ExitCode::FAILURE);
+        }
+        if !json {
+            return Ok(ExitCode::SUCCESS);
+        }
+    }
+    Ok(crate::emit(&profile, json))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn profile_for_a_format() {
+        let design = Design {
+            name: "Zero".to_string(),
+            focal_length: 25.,
+            diameter: 0.18,
+            thickness: 0.05,
+            ..Default::default()
+        };
+        let format = pinhole::formats::find("35mm").unwrap();
+        let p = profile(&design, format, "Zero Image", "Sony E");
+        assert_eq!(p.model, "Zero 25mm f/139");
+        assert!((p.crop_factor - 1.).abs() < 1e-4);
+        assert_eq!(p.k1, pinhole::lensfun::fit(&design.point(), format).k1);
+        assert_eq!(p.to_string(), p.xml.trim_end());
+        assert!(p.xml.contains("<mount>Sony E</mount>"));
+    }
+}
@@ -0,0 +1,110 @@
+use std::fmt::Display;
+use std::process::ExitCode;
+
+use clap::{CommandFactory, Parser, Subcommand, error::ErrorKind};
+use serde::Serialize;
+
+mod calc;
+mod card;
+mod correct;
+mod fading;
+mod flatfield;
+mod lensfun;
+mod solargraph;
+mod sweep;
+mod tag;
+mod units;
+
+/// Pinhole calculations on the command line.
+#[derive(Parser, Debug)]
+#[command(
+    version,
+    after_help = "Lengths take um, mm, cm, m or in (bare numbers are mm), wavelengths nm,\n\
+                  distances m (or inf) and times s, min or h.\n\n\
+                  Exit status is 0 on success, 1 when output fails and 2 on invalid input."
+)]
+struct Cli {
+    /// Print the results as JSON
+    #[arg(long, global = true)]
+    json: bool,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Optimal pinhole diameter for a focal length
+    Optimal(calc::OptimalArgs),
+    /// View angle of a pinhole in a plate
+    Angle(calc::AngleArgs),
+    /// Corner falloff for a format or projection diameter
+    Vignetting(calc::VignettingArgs),
+    /// Diameter covered by the view angle at the film
+    Coverage(calc::CoverageArgs),
+    /// F-number and exposure factor
+    Exposure(calc::ExposureArgs),
+    /// List the film formats, or show one
+    Format(calc::FormatArgs),
+    /// List the filters, or what a stack of them does
+    Filter(calc::FilterArgs),
+    /// Table of results for every combination of input values
+    Sweep(sweep::SweepArgs),
+    /// Image of the predicted illumination over a format, to divide scans by
+    Flatfield(flatfield::FlatfieldArgs),
+    /// Take the predicted falloff out of scans
+    Correct(correct::CorrectArgs),
+    /// Lensfun vignetting profile for a design
+    Lensfun(lensfun::LensfunArgs),
+    /// Exposure time as the light changes, from readings or the sun
+    Fading(fading::FadingArgs),
+    /// Exposure card for the camera, as SVG or PDF
+    Card(card::CardArgs),
+    /// XMP sidecars, or EXIF in place, with the camera for scans
+    Tag(tag::TagArgs),
+    /// Sun trails on paper over weeks or months, with a preview image
+    Solargraph(solargraph::SolargraphArgs),
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let result = match &cli.command {
+        Command::Optimal(args) => calc::optimal(args).map(|r| emit(&r, cli.json)),
+        Command::Angle(args) => Ok(emit(&calc::angle(args), cli.json)),
+        Command::Vignetting(args) => calc::vignetting(args).map(|r| emit(&r, cli.json)),
+        Command::Coverage(args) => calc::coverage(args).map(|r| emit(&r, cli.json)),
+        Command::Exposure(args) => Ok(emit(&calc::exposure(args), cli.json)),
+        Command::Format(args) => calc::format(args).map(|r| emit(&r, cli.json)),
+        Command::Filter(args) => calc::filter(args).map(|r| emit(&r, cli.json)),
+        Command::Sweep(args) => sweep::run(args, cli.json),
+        Command::Flatfield(args) => flatfield::run(args, cli.json),
+        Command::Correct(args) => correct::run(args, cli.json),
+        Command::Lensfun(args) => lensfun::run(args, cli.json),
+        Command::Fading(args) => fading::run(args, cli.json),
+        Command::Card(args) => card::run(args, cli.json),
+        Command::Tag(args) => tag::run(args, cli.json),
+        Command::Solargraph(args) => solargraph::run(args, cli.json),
+    };
+    match result {
+        Ok(code) => code,
+        // Same exit status and message layout as clap's own argument errors.
+        Err(msg) => Cli::command().error(ErrorKind::ValueValidation, msg).exit(),
+    }
+}
+
+fn emit<T: Serialize + Display>(result: &T, json: bool) -> ExitCode {
+    let out = if json {
+        serde_json::to_string_pretty(result).map_err(|e| e.to_string())
+    } else {
+        Ok(result.to_string())
+    };
+    match out {
+        Ok(out) => {
+            println!("{out}");
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
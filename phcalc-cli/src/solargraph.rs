@@ -0,0 +1,306 @@
+use std::fmt;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::Args;
+use image::{Rgb, RgbImage};
+use pinhole::solargraphy::{self, Camera, Day, Mount, Plan};
+use pinhole::sun::{Date, Place};
+use serde::Serialize;
+
+use crate::flatfield::{format, load_design};
+use crate::units;
+
+#[derive(Args, Debug)]
+pub struct SolargraphArgs {
+    /// Design file (.pinhole.toml or .json)
+    #[arg(long)]
+    design: PathBuf,
+    /// Paper format, default the one in the design
+    #[arg(long)]
+    format: Option<String>,
+    /// Latitude in degrees, north positive
+    #[arg(long, allow_negative_numbers = true)]
+    latitude: f64,
+    /// Longitude in degrees, east positive
+    #[arg(long, allow_negative_numbers = true)]
+    longitude: f64,
+    /// First day of the exposure, e.g. 2025-06-21
+    #[arg(long)]
+    from: Date,
+    /// Last day of the exposure
+    #[arg(long)]
+    to: Date,
+    /// Direction the camera faces, degrees clockwise from north
+    #[arg(long, default_value_t = 180.)]
+    facing: f64,
+    /// Tilt of the camera above the horizon in degrees
+    #[arg(long, default_value_t = 0., allow_negative_numbers = true)]
+    tilt: f64,
+    /// Minutes between sun positions
+    #[arg(long, default_value_t = 2)]
+    step: u32,
+    /// Pixel size of the preview on the paper
+    #[arg(long, default_value = "0.1mm", value_parser = units::length)]
+    pixel: f32,
+    /// Preview image to write, .png, .jpg or .tif
+    #[arg(long, short)]
+    out: PathBuf,
+}
+
+#[derive(Serialize)]
+pub struct DayRow {
+    date: String,
+    minutes: u32,
+    hours: f32,
+    total_hours: f32,
+}
+
+#[derive(Serialize)]
+pub struct Solstice {
+    name: &'static str,
+    date: String,
+    minutes: u32,
+    // The trace at noon, mm right of and above the centre.
+    noon_mm: Option<[f32; 2]>,
+}
+
+#[derive(Serialize)]
+pub struct Solargraph {
+    path: PathBuf,
+    width: u32,
+    height: u32,
+    hours: f32,
+    days: Vec<DayRow>,
+    solstices: Vec<Solstice>,
+}
+
+impl fmt::Display for Solargraph {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Date        On paper  Full sun  Total")?;
+        for d in &self.days {
+            writeln!(
+                f,
+                "{}  {:>5} min  {:>6.2} h  {:>5.1} h",
+                d.date, d.minutes, d.hours, d.total_hours
+            )?;
+        }
+        for s in &self.solstices {
+            match s.noon_mm {
+                Some([x, y]) => writeln!(
+                    f,
+                    "{} solstice {}: {} min on the paper, at noon {x:.1} mm across and {y:.1} mm up",
+                    s.name, s.date, s.minutes
+                )?,
+                None => writeln!(f, "{} solstice {}: misses the paper", s.name, s.date)?,
+            }
+        }
+        write!(
+            f,
+            "Wrote a {} x {} preview to {}, {:.1} hours of full sun in all",
+            self.width,
+            self.height,
+            self.path.display(),
+            self.hours
+        )
+    }
+}
+
+const PAPER: Rgb<u8> = Rgb([28, 22, 30]);
+const TRAIL: [f32; 3] = [255., 196., 120.];
+const SOLSTICE: Rgb<u8> = Rgb([90, 170, 255]);
+// Minutes of full sun that take the paper most of the way to black on the
+// negative, bright here.
+const BURN: f32 = 1.5;
+
+// Some 400 MB of image and burn, a smaller pixel than anyone scans at.
+const MAX_PIXELS: usize = 50_000_000;
+
+// The trails as the scan of the paper would show them, the solstices drawn
+// over them in blue and outside the pinhole's coverage left black. Too many
+// pixels for the paper is an error.
+pub fn preview(plan: &Plan, camera: &Camera, pixel: f32, step: u32) -> Result<RgbImage, String> {
+    let side = |half: f64| (2. * half as f32 / pixel).round().max(1.);
+    let (w, h) = (side(camera.half_width), side(camera.half_height));
+    let too_large =
+        || format!("a {w:.0} x {h:.0} pixel preview is too large, give a larger --pixel");
+    // Casts saturate, the product catches them.
+    let pixels = (w as usize)
+        .checked_mul(h as usize)
+        .filter(|n| *n <= MAX_PIXELS)
+        .ok_or_else(too_large)?;
+    let (width, height) = (w as u32, h as u32);
+    let index = |px: usize, py: usize| py * width as usize + px;
+    let to_pixel = |x: f32, y: f32| {
+        (
+            (x + camera.half_width as f32) / pixel,
+            (camera.half_height as f32 - y) / pixel,
+        )
+    };
+    // The sun is half a degree across.
+    let radius = (camera.focal_length as f32 * 0.267f32.to_radians().tan() / pixel).max(0.7);
+    let mut burn = vec![0f32; pixels];
+    let r = radius.ceil() as i64;
+    for day in &plan.days {
+        along(day, step, &to_pixel, |x, y, minutes| {
+            let (cx, cy) = (x.floor() as i64, y.floor() as i64);
+            for py in cy - r..=cy + r {
+                for px in cx - r..=cx + r {
+                    let inside = (px as f32 + 0.5 - x).hypot(py as f32 + 0.5 - y) <= radius;
+                    if inside && (0..width as i64).contains(&px) && (0..height as i64).contains(&py)
+                    {
+                        burn[index(px as usize, py as usize)] += minutes;
+                    }
+                }
+            }
+        });
+    }
+    let mut solstice = vec![false; burn.len()];
+    for (_, day) in &plan.solstices {
+        along(day, step, &to_pixel, |x, y, _| {
+            let (px, py) = (x as u32, y as u32);
+            if px < width && py < height {
+                solstice[index(px as usize, py as usize)] = true;
+            }
+        });
+    }
+    Ok(RgbImage::from_fn(width, height, |px, py| {
+        let i = index(px as usize, py as usize);
+        let x = (px as f32 + 0.5) * pixel - camera.half_width as f32;
+        let y = camera.half_height as f32 - (py as f32 + 0.5) * pixel;
+        if solstice[i] {
+            return SOLSTICE;
+        }
+        if x.hypot(y) > camera.coverage_radius as f32 {
+            return Rgb([0, 0, 0]);
+        }
+        let t = 1. - (-burn[i] / BURN).exp();
+        Rgb(std::array::from_fn(|c| {
+            (PAPER.0[c] as f32 + t * (TRAIL[c] - PAPER.0[c] as f32)).round() as u8
+        }))
+    }))
+}
+
+// Every pixel or so along a day's trail, in pixels, with the minutes of full
+// sun that fell there.
+fn along(
+    day: &Day,
+    step: u32,
+    to_pixel: &impl Fn(f32, f32) -> (f32, f32),
+    mut at: impl FnMut(f32, f32, f32),
+) {
+    for arc in &day.arcs {
+        for pair in arc.windows(2) {
+            let (x0, y0) = to_pixel(pair[0].x, pair[0].y);
+            let (x1, y1) = to_pixel(pair[1].x, pair[1].y);
+            let n = (x1 - x0).hypot(y1 - y0).ceil().max(1.) as usize;
+            let minutes = pair[0].light * step as f32 / n as f32;
+            for i in 0..n {
+                let t = i as f32 / n as f32;
+                at(x0 + t * (x1 - x0), y0 + t * (y1 - y0), minutes);
+            }
+        }
+    }
+}
+
+pub fn run(args: &SolargraphArgs, json: bool) -> Result<ExitCode, String> {
+    if !(-90.0..=90.).contains(&args.latitude) {
+        return Err(format!(
+            "latitude {} is not between -90 and 90",
+            args.latitude
+        ));
+    }
+    if args.to < args.from {
+        return Err(format!("--to {} is before --from {}", args.to, args.from));
+    }
+    let design = load_design(&args.design)?;
+    let format = format(&design, args.format.as_deref())?;
+    let camera = Camera::new(&design.point(), format);
+    let mount = Mount {
+        facing: args.facing,
+        tilt: args.tilt,
+    };
+    let place = Place {
+        latitude: args.latitude,
+        longitude: args.longitude,
+    };
+    let plan = solargraphy::plan(&camera, &mount, &place, args.from, args.to, args.step);
+    let image = preview(&plan, &camera, args.pixel, args.step.max(1))?;
+    if let Err(e) = image.save(&args.out) {
+        eprintln!("error: cannot write {}: {e}", args.out.display());
+        return Ok(ExitCode::FAILURE);
+    }
+    let mut total = 0.;
+    let days = plan
+        .days
+        .iter()
+        .map(|d| {
+            total += d.hours;
+            DayRow {
+                date: d.date.to_string(),
+                minutes: d.minutes,
+                hours: d.hours,
+                total_hours: total,
+            }
+        })
+        .collect();
+    let solstices = plan
+        .solstices
+        .iter()
+        .map(|(name, d)| Solstice {
+            name,
+            date: d.date.to_string(),
+            minutes: d.minutes,
+            noon_mm: d.noon.map(|(x, y)| [x, y]),
+        })
+        .collect();
+    Ok(crate::emit(
+        &Solargraph {
+            path: args.out.clone(),
+            width: image.width(),
+            height: image.height(),
+            hours: plan.hours(),
+            days,
+            solstices,
+        },
+        json,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trails_in_the_preview() {
+        let p = pinhole::sweep::Point {
+            focal_length: 25.,
+            diameter: 0.3,
+            thickness: 0.1,
+            ..Default::default()
+        };
+        let camera = Camera::new(&p, pinhole::formats::find("6x9").unwrap());
+        let mount = Mount {
+            facing: 180.,
+            tilt: 30.,
+        };
+        let place = Place {
+            latitude: 51.5,
+            longitude: 0.,
+        };
+        let from = Date::new(2025, 3, 1);
+        let plan = solargraphy::plan(&camera, &mount, &place, from, from.add_days(6), 4);
+        let image = preview(&plan, &camera, 0.5, 4).unwrap();
+        assert_eq!(image.dimensions(), (180, 120));
+        // Burnt along the noon trail, bare paper well above it.
+        let (x, y) = plan.days[3].noon.unwrap();
+        let px = ((x + 45.) / 0.5) as u32;
+        let py = ((30. - y) / 0.5) as u32;
+        assert!(image.get_pixel(px, py).0[0] > 150);
+        assert_eq!(*image.get_pixel(px, 2), PAPER);
+        // A micron a pixel is 90000 x 60000.
+        let err = preview(&plan, &camera, 0.001, 4).unwrap_err();
+        assert!(err.contains("90000 x 60000"), "{err}");
+        assert!(preview(&plan, &camera, 0., 4).is_err());
+    }
+}
@@ -0,0 +1,205 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::{Args, ValueEnum};
+use pinhole::sweep::{Column, Output, Param, Point, Sweep, Values};
+
+use crate::units;
+
+#[derive(Args, Debug)]
+#[command(
+    after_help = "Values are a list (25mm,50mm,75mm) or an inclusive range with a \
+                        step (25mm..300mm:5mm).\nInputs that are not swept take the GUI \
+                        defaults."
+)]
+pub struct SweepArgs {
+    /// Film formats, a list of names or "all"
+    #[arg(long)]
+    format: Option<String>,
+    /// Focal lengths
+    #[arg(long, value_parser = lengths)]
+    focal: Option<Values>,
+    /// Pinhole diameters
+    #[arg(long, value_parser = lengths)]
+    diameter: Option<Values>,
+    /// Pinhole plate thicknesses
+    #[arg(long, value_parser = lengths)]
+    thickness: Option<Values>,
+    /// Projection diameters
+    #[arg(long, conflicts_with = "format", value_parser = lengths)]
+    projection: Option<Values>,
+    /// Wavelengths
+    #[arg(long, value_parser = wavelengths)]
+    wavelength: Option<Values>,
+    /// Rayleigh factors
+    #[arg(long, value_parser = factors)]
+    rayleigh: Option<Values>,
+    /// Subject distances
+    #[arg(long, value_parser = distances)]
+    distance: Option<Values>,
+    /// Columns to write, comma separated (default: the swept inputs and main results)
+    #[arg(long, value_delimiter = ',', value_parser = column)]
+    columns: Vec<Column>,
+    /// Output format
+    #[arg(long, value_enum, default_value_t = OutputArg::Csv)]
+    output: OutputArg,
+    /// Write to a file instead of standard output
+    #[arg(long, short)]
+    out: Option<PathBuf>,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum OutputArg {
+    Csv,
+    Tsv,
+    Json,
+}
+
+fn column(s: &str) -> Result<Column, String> {
+    Column::from_name(s).ok_or_else(|| {
+        let names: Vec<&str> = Column::ALL.iter().map(Column::name).collect();
+        format!("unknown column '{s}' (one of {})", names.join(", "))
+    })
+}
+
+// Parse a list or range, every number with the unit parser of the input.
+// Ranges that give no values are refused here, before anything runs.
+fn values(spec: &str, parse: fn(&str) -> Result<f32, String>) -> Result<Values, String> {
+    match spec.split_once("..") {
+        Some((start, rest)) => {
+            let (end, step) = rest
+                .rsplit_once(':')
+                .ok_or_else(|| format!("range '{spec}' needs a step, like 25mm..300mm:5mm"))?;
+            let range = Values::Range {
+                start: parse(start)?,
+                end: parse(end)?,
+                step: parse(step)?,
+            };
+            range.to_vec()?;
+            Ok(range)
+        }
+        None => Ok(Values::List(
+            spec.split(',').map(parse).collect::<Result<_, _>>()?,
+        )),
+    }
+}
+
+fn lengths(spec: &str) -> Result<Values, String> {
+    values(spec, units::length)
+}
+
+fn wavelengths(spec: &str) -> Result<Values, String> {
+    values(spec, units::wavelength)
+}
+
+fn factors(spec: &str) -> Result<Values, String> {
+    values(spec, units::factor)
+}
+
+fn distances(spec: &str) -> Result<Values, String> {
+    values(spec, units::distance)
+}
+
+pub fn run(args: &SweepArgs, json: bool) -> Result<ExitCode, String> {
+    let mut sweep = Sweep::new(Point::default());
+    let mut columns = Vec::new();
+    if let Some(spec) = &args.format {
+        sweep = if spec == "all" {
+            sweep.all_formats()
+        } else {
+            sweep.formats(
+                spec.split(',')
+                    .map(|n| {
+                        pinhole::formats::find(n).ok_or_else(|| format!("unknown format '{n}'"))
+                    })
+                    .collect::<Result<_, _>>()?,
+            )
+        };
+        columns.push(Column::Format);
+    }
+    let params = [
+        (&args.focal, Param::FocalLength),
+        (&args.diameter, Param::Diameter),
+        (&args.thickness, Param::Thickness),
+        (&args.projection, Param::Projection),
+        (&args.wavelength, Param::Wavelength),
+        (&args.rayleigh, Param::RayleighFactor),
+        (&args.distance, Param::SubjectDistance),
+    ];
+    for (values, param) in params {
+        if let Some(values) = values {
+            sweep = sweep.param(param, values)?;
+            columns.push(param.column());
+        }
+    }
+    if !args.columns.is_empty() {
+        columns = args.columns.clone();
+    } else {
+        columns.extend([
+            Column::FNumber,
+            Column::ViewAngle,
+            Column::OptimalDiameter,
+            Column::VignettingStops,
+        ]);
+    }
+    let output = match args.output {
+        _ if json => Output::Json,
+        OutputArg::Csv => Output::Csv,
+        OutputArg::Tsv => Output::Tsv,
+        OutputArg::Json => Output::Json,
+    };
+
+    let result = match &args.out {
+        Some(path) => File::create(path).and_then(|f| {
+            let mut w = BufWriter::new(f);
+            pinhole::sweep::write(&mut w, &sweep, &columns, output)?;
+            w.flush()
+        }),
+        None => {
+            let mut w = BufWriter::new(io::stdout().lock());
+            pinhole::sweep::write(&mut w, &sweep, &columns, output).and_then(|_| w.flush())
+        }
+    };
+    match result {
+        Ok(()) => Ok(ExitCode::SUCCESS),
+        // Piped into head or similar, nothing to complain about.
+        Err(e) if e.kind() == io::ErrorKind::BrokenPipe => Ok(ExitCode::SUCCESS),
+        Err(e) => {
+            eprintln!("error: {e}");
+            Ok(ExitCode::FAILURE)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn range_with_units() {
+        let v = values("2.5cm..30cm:5mm", units::length).unwrap();
+        assert_eq!(
+            v,
+            Values::Range {
+                start: 25.,
+                end: 300.,
+                step: 5.
+            }
+        );
+    }
+
+    #[test]
+    fn list_with_units() {
+        let v = values("1m,inf", units::distance).unwrap();
+        assert_eq!(v, Values::List(vec![1., f32::INFINITY]));
+    }
+
+    #[test]
+    fn ranges_that_go_nowhere() {
+        let err = lengths("300mm..25mm:5mm").unwrap_err();
+        assert!(err.contains("downwards"), "{err}");
+        assert!(lengths("25mm..300mm:0mm").is_err());
+    }
+}
@@ -0,0 +1,384 @@
+use std::fmt;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use clap::{Args, ValueEnum};
+use exif::experimental::Writer;
+use exif::{Field, In, Rational, Tag, Value};
+use img_parts::{Bytes, DynImage, ImageEXIF};
+use pinhole::metadata::Metadata;
+use serde::Serialize;
+
+use crate::flatfield::load_design;
+use crate::units;
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum Sidecar {
+    /// scan.tif.xmp, as darktable and digiKam look for it
+    Append,
+    /// scan.xmp, as Lightroom and Capture One look for it
+    Replace,
+}
+
+#[derive(Args, Debug)]
+pub struct TagArgs {
+    /// Scans to describe
+    #[arg(required = true)]
+    files: Vec<PathBuf>,
+    /// Design file (.pinhole.toml or .json)
+    #[arg(long)]
+    design: PathBuf,
+    /// Exposure time, e.g. 4s or 2min
+    #[arg(long, value_parser = units::time)]
+    exposure: Option<f32>,
+    /// Film stock, default the one in the design
+    #[arg(long)]
+    film: Option<String>,
+    /// Lens name, default "<design name> <focal length>mm f/<number>"
+    #[arg(long)]
+    lens_model: Option<String>,
+    #[arg(long, value_enum, default_value_t = Sidecar::Append)]
+    sidecar: Sidecar,
+    /// Replace sidecars that are already there, edits saved in them are lost
+    #[arg(long)]
+    force: bool,
+    /// Also write the tags into the EXIF of JPEG and PNG files. TIFF and
+    /// other scans get only the .xmp sidecar
+    #[arg(long)]
+    exif: bool,
+}
+
+#[derive(Serialize)]
+pub struct TaggedFile {
+    input: PathBuf,
+    sidecar: PathBuf,
+    exif: bool,
+}
+
+#[derive(Serialize)]
+pub struct Tagged {
+    lens_model: String,
+    focal_length_mm: f32,
+    fnumber: f32,
+    focal_length_35mm: u16,
+    film: String,
+    exposure_s: Option<f32>,
+    files: Vec<TaggedFile>,
+}
+
+impl fmt::Display for Tagged {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, t) in self.files.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{} -> {}", t.input.display(), t.sidecar.display())?;
+            if t.exif {
+                write!(f, " and EXIF")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+pub fn sidecar_path(path: &Path, sidecar: Sidecar) -> PathBuf {
+    match sidecar {
+        Sidecar::Append => {
+            let mut name = path.as_os_str().to_owned();
+            name.push(".xmp");
+            PathBuf::from(name)
+        }
+        Sidecar::Replace => path.with_extension("xmp"),
+    }
+}
+
+fn rational(v: [u32; 2]) -> Value {
+    Value::Rational(vec![Rational {
+        num: v[0],
+        denom: v[1],
+    }])
+}
+
+// UserComment starts with its character code, UCS-2 in the byte order of the
+// EXIF block when it is not plain ASCII.
+fn user_comment(text: &str, little_endian: bool) -> Value {
+    let mut bytes = Vec::new();
+    if text.is_ascii() {
+        bytes.extend_from_slice(b"ASCII\0\0\0");
+        bytes.extend_from_slice(text.as_bytes());
+    } else {
+        bytes.extend_from_slice(b"UNICODE\0");
+        for u in text.encode_utf16() {
+            let b = if little_endian {
+                u.to_le_bytes()
+            } else {
+                u.to_be_bytes()
+            };
+            bytes.extend_from_slice(&b);
+        }
+    }
+    Value::Undefined(bytes, 0)
+}
+
+pub fn exif_fields(m: &Metadata, little_endian: bool) -> Vec<Field> {
+    let field = |tag, value| Field {
+        tag,
+        ifd_num: In::PRIMARY,
+        value,
+    };
+    let mut fields = vec![
+        field(
+            Tag::FocalLength,
+            rational(pinhole::metadata::rational(m.focal_length)),
+        ),
+        field(
+            Tag::FNumber,
+            rational(pinhole::metadata::rational(m.fnumber)),
+        ),
+        field(
+            Tag::FocalLengthIn35mmFilm,
+            Value::Short(vec![m.focal_length_35mm]),
+        ),
+        field(
+            Tag::LensModel,
+            Value::Ascii(vec![m.lens_model.clone().into_bytes()]),
+        ),
+    ];
+    if let Some(t) = m.exposure_time {
+        fields.push(field(
+            Tag::ExposureTime,
+            rational(pinhole::metadata::exposure_rational(t)),
+        ));
+    }
+    if let Some(comment) = m.comment() {
+        fields.push(field(
+            Tag::UserComment,
+            user_comment(&comment, little_endian),
+        ));
+    }
+    fields
+}
+
+// The EXIF block with our tags in place of any there were, keeping the rest of
+// the main image's tags. The thumbnail is dropped.
+pub fn merge_exif(old: Option<&[u8]>, m: &Metadata) -> Result<Vec<u8>, String> {
+    let old = match old {
+        Some(buf) => Some(
+            exif::Reader::new()
+                .read_raw(buf.to_vec())
+                .map_err(|e| format!("cannot read the EXIF: {e}"))?,
+        ),
+        None => None,
+    };
+    let little_endian = old.as_ref().is_some_and(|e| e.little_endian());
+    let ours = exif_fields(m, little_endian);
+    let mut writer = Writer::new();
+    for f in &ours {
+        writer.push_field(f);
+    }
+    if let Some(old) = &old {
+        for f in old.fields() {
+            if f.ifd_num == In::PRIMARY && !ours.iter().any(|o| o.tag == f.tag) {
+                writer.push_field(f);
+            }
+        }
+    }
+    let mut buf = Cursor::new(Vec::new());
+    writer
+        .write(&mut buf, little_endian)
+        .map_err(|e| format!("cannot write the EXIF: {e}"))?;
+    Ok(buf.into_inner())
+}
+
+fn write_exif(path: &Path, m: &Metadata) -> Result<(), String> {
+    let bytes = std::fs::read(path).map_err(|e| e.to_string())?;
+    let mut img = DynImage::from_bytes(Bytes::from(bytes))
+        .map_err(|e| e.to_string())?
+        .ok_or("EXIF can only be written into JPEG and PNG, this file got only the .xmp sidecar")?;
+    let exif = merge_exif(img.exif().as_deref(), m)?;
+    img.set_exif(Some(Bytes::from(exif)));
+    let mut out = Vec::new();
+    img.encoder()
+        .write_to(&mut out)
+        .map_err(|e| e.to_string())?;
+    // Next to the scan and renamed over it, so a full disk or a crash leaves
+    // the scan as it was.
+    let name = path.file_name().ok_or("no file name")?.to_string_lossy();
+    let tmp = path.with_file_name(format!(".{name}.phcalc-tmp"));
+    std::fs::write(&tmp, out)
+        .and_then(|()| std::fs::rename(&tmp, path))
+        .map_err(|e| {
+            let _ = std::fs::remove_file(&tmp);
+            e.to_string()
+        })
+}
+
+fn tag_file(path: &Path, args: &TagArgs, m: &Metadata) -> Result<TaggedFile, String> {
+    if !path.is_file() {
+        return Err("no such file".to_string());
+    }
+    let sidecar = sidecar_path(path, args.sidecar);
+    if sidecar.exists() && !args.force {
+        return Err(format!(
+            "{} is already there, give --force to replace it",
+            sidecar.display()
+        ));
+    }
+    std::fs::write(&sidecar, m.to_xmp())
+        .map_err(|e| format!("cannot write {}: {e}", sidecar.display()))?;
+    Ok(TaggedFile {
+        input: path.to_path_buf(),
+        sidecar,
+        exif: false,
+    })
+}
+
+pub fn run(args: &TagArgs, json: bool) -> Result<ExitCode, String> {
+    let mut design = load_design(&args.design)?;
+    if let Some(film) = &args.film {
+        design.film = film.clone();
+    }
+    let mut m = Metadata::new(&design);
+    m.exposure_time = args.exposure;
+    if let Some(lens_model) = &args.lens_model {
+        m.lens_model = lens_model.clone();
+    }
+    let mut files = Vec::new();
+    let mut failed = false;
+    // Like correct, a file that fails does not stop the batch.
+    for path in &args.files {
+        match tag_file(path, args, &m) {
+            Ok(mut t) => {
+                // The sidecar is written even when the EXIF cannot be.
+                if args.exif {
+                    match write_exif(path, &m) {
+                        Ok(()) => t.exif = true,
+                        Err(e) => {
+                            eprintln!("error: {}: {e}", path.display());
+                            failed = true;
+                        }
+                    }
+                }
+                files.push(t);
+            }
+            Err(e) => {
+                eprintln!("error: {}: {e}", path.display());
+                failed = true;
+            }
+        }
+    }
+    let code = crate::emit(
+        &Tagged {
+            lens_model: m.lens_model.clone(),
+            focal_length_mm: m.focal_length,
+            fnumber: m.fnumber,
+            focal_length_35mm: m.focal_length_35mm,
+            film: m.film.clone(),
+            exposure_s: m.exposure_time,
+            files,
+        },
+        json,
+    );
+    Ok(if failed { ExitCode::FAILURE } else { code })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metadata() -> Metadata {
+        let design = pinhole::design::Design {
+            name: "Zero".to_string(),
+            focal_length: 25.,
+            diameter: 0.18,
+            film: "Fomapan 100".to_string(),
+            ..Default::default()
+        };
+        Metadata {
+            exposure_time: Some(8.),
+            ..Metadata::new(&design)
+        }
+    }
+
+    #[test]
+    fn sidecar_names() {
+        let p = Path::new("scans/roll1-03.tif");
+        assert_eq!(
+            sidecar_path(p, Sidecar::Append),
+            Path::new("scans/roll1-03.tif.xmp")
+        );
+        assert_eq!(
+            sidecar_path(p, Sidecar::Replace),
+            Path::new("scans/roll1-03.xmp")
+        );
+    }
+
+    #[test]
+    fn exif_roundtrip() {
+        // A scanner's tag survives, ours replace the old ones.
+        let make = Field {
+            tag: Tag::Make,
+            ifd_num: In::PRIMARY,
+            value: Value::Ascii(vec![b"Epson".to_vec()]),
+        };
+        let old_fnumber = Field {
+            tag: Tag::FNumber,
+            ifd_num: In::PRIMARY,
+            value: rational([8, 1]),
+        };
+        let mut writer = Writer::new();
+        writer.push_field(&make);
+        writer.push_field(&old_fnumber);
+        let mut old = Cursor::new(Vec::new());
+        writer.write(&mut old, true).unwrap();
+
+        let buf = merge_exif(Some(&old.into_inner()), &metadata()).unwrap();
+        let exif = exif::Reader::new().read_raw(buf).unwrap();
+        let get = |tag| {
+            let f = exif.get_field(tag, In::PRIMARY).unwrap();
+            f.display_value().to_string()
+        };
+        assert!(exif.little_endian());
+        assert_eq!(get(Tag::Make), "\"Epson\"");
+        assert_eq!(get(Tag::FNumber), "138.9");
+        assert_eq!(get(Tag::ExposureTime), "8");
+        assert_eq!(get(Tag::FocalLengthIn35mmFilm), "25");
+        let comment = exif.get_field(Tag::UserComment, In::PRIMARY).unwrap();
+        assert!(
+            matches!(&comment.value, Value::Undefined(b, _) if b == b"ASCII\0\0\0Film: Fomapan 100")
+        );
+    }
+
+    #[test]
+    fn exif_in_place() {
+        let dir = std::env::temp_dir();
+        for name in ["phcalc-tag-test.jpg", "phcalc-tag-test.png"] {
+            let path = dir.join(name);
+            image::RgbImage::from_pixel(16, 8, image::Rgb([100, 120, 140]))
+                .save(&path)
+                .unwrap();
+            write_exif(&path, &metadata()).unwrap();
+            let file = std::fs::File::open(&path).unwrap();
+            let exif = exif::Reader::new()
+                .read_from_container(&mut std::io::BufReader::new(file))
+                .unwrap();
+            // Still an image.
+            assert_eq!(image::open(&path).unwrap().width(), 16);
+            std::fs::remove_file(&path).unwrap();
+            assert!(!dir.join(format!(".{name}.phcalc-tmp")).exists());
+            let lens = exif.get_field(Tag::LensModel, In::PRIMARY).unwrap();
+            assert_eq!(lens.display_value().to_string(), "\"Zero 25mm f/139\"");
+        }
+        // A TIFF is left alone, the error says it only has the sidecar.
+        let path = dir.join("phcalc-tag-test.tif");
+        image::RgbImage::from_pixel(16, 8, image::Rgb([100, 120, 140]))
+            .save(&path)
+            .unwrap();
+        let before = std::fs::read(&path).unwrap();
+        let e = write_exif(&path, &metadata()).unwrap_err();
+        assert!(e.contains("only the .xmp sidecar"), "{e}");
+        assert_eq!(std::fs::read(&path).unwrap(), before);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,169 @@
+// Parsers for command line values with unit suffixes. A bare number is taken
+// in the unit the GUI uses for that value.
+
+// Split "0.3mm" into (0.3, "mm"). The number may be a fraction, like "1/125".
+fn split_unit(s: &str) -> Result<(f32, String), String> {
+    let s = s.trim();
+    let end = s
+        .find(|c: char| !(c.is_ascii_digit() || c == '.' || c == '/' || c == '-' || c == '+'))
+        .unwrap_or(s.len());
+    let (num, unit) = s.split_at(end);
+    let value = match num.split_once('/') {
+        Some((a, b)) => parse_number(a)? / parse_number(b)?,
+        None => parse_number(num)?,
+    };
+    Ok((value, unit.trim().to_lowercase()))
+}
+
+fn parse_number(s: &str) -> Result<f32, String> {
+    s.parse::<f32>()
+        .map_err(|_| format!("'{s}' is not a number"))
+}
+
+fn positive(value: f32, s: &str) -> Result<f32, String> {
+    if value.is_finite() && value > 0. {
+        Ok(value)
+    } else {
+        Err(format!("'{s}' must be larger than zero"))
+    }
+}
+
+// Length in mm.
+pub fn length(s: &str) -> Result<f32, String> {
+    let (value, unit) = split_unit(s)?;
+    let mm = match unit.as_str() {
+        "" | "mm" => value,
+        "um" | "µm" | "micron" => value / 1000.,
+        "cm" => value * 10.,
+        "m" => value * 1000.,
+        "in" | "\"" => value * 25.4,
+        _ => {
+            return Err(format!(
+                "unknown length unit '{unit}' (use um, mm, cm, m or in)"
+            ));
+        }
+    };
+    positive(mm, s)
+}
+
+// Subject distance in m, "inf" for infinity.
+pub fn distance(s: &str) -> Result<f32, String> {
+    if matches!(s.trim().to_lowercase().as_str(), "inf" | "infinity" | "∞") {
+        return Ok(f32::INFINITY);
+    }
+    let (value, unit) = split_unit(s)?;
+    let m = match unit.as_str() {
+        "" | "m" => value,
+        "mm" => value / 1000.,
+        "cm" => value / 100.,
+        "in" => value * 0.0254,
+        "ft" => value * 0.3048,
+        _ => {
+            return Err(format!(
+                "unknown distance unit '{unit}' (use mm, cm, m, in or ft)"
+            ));
+        }
+    };
+    positive(m, s)
+}
+
+// Wavelength in nm.
+pub fn wavelength(s: &str) -> Result<f32, String> {
+    let (value, unit) = split_unit(s)?;
+    let nm = match unit.as_str() {
+        "" | "nm" => value,
+        "um" | "µm" => value * 1000.,
+        _ => return Err(format!("unknown wavelength unit '{unit}' (use nm or um)")),
+    };
+    positive(nm, s)
+}
+
+// Time in seconds, "1/125", "1/125s", "30s", "4min" or "1h".
+pub fn time(s: &str) -> Result<f32, String> {
+    let (value, unit) = split_unit(s)?;
+    let secs = match unit.as_str() {
+        "" | "s" | "sec" => value,
+        "min" => value * 60.,
+        "h" => value * 3600.,
+        _ => return Err(format!("unknown time unit '{unit}' (use s, min or h)")),
+    };
+    positive(secs, s)
+}
+
+// Time of day as "20:15" or "20:15:30", in hours.
+pub fn clock(s: &str) -> Result<f64, String> {
+    let invalid = || format!("'{s}' is not a time of day like 20:15");
+    let parts: Vec<_> = s.trim().split(':').map(str::parse::<u32>).collect();
+    let (h, m, sec) = match parts[..] {
+        [Ok(h), Ok(m)] => (h, m, 0),
+        [Ok(h), Ok(m), Ok(sec)] => (h, m, sec),
+        _ => return Err(invalid()),
+    };
+    if h > 23 || m > 59 || sec > 59 {
+        return Err(invalid());
+    }
+    Ok(h as f64 + m as f64 / 60. + sec as f64 / 3600.)
+}
+
+// A light reading as "time=EV", the time from the start, e.g. "10min=8.5".
+pub fn reading(s: &str) -> Result<(f32, f32), String> {
+    let Some((t, ev)) = s.split_once('=') else {
+        return Err(format!("'{s}' is not a reading like 10min=8.5"));
+    };
+    let t = if t.trim() == "0" { 0. } else { time(t)? };
+    let ev = ev
+        .trim()
+        .parse::<f32>()
+        .map_err(|_| format!("'{ev}' is not an EV"))?;
+    Ok((t, ev))
+}
+
+// An f-stop, written as "f/32", "f32" or "32".
+pub fn fstop(s: &str) -> Result<f32, String> {
+    let t = s.trim().to_lowercase();
+    let t = t.trim_start_matches("f/").trim_start_matches('f');
+    positive(parse_number(t)?, s)
+}
+
+// A plain number without a unit.
+pub fn factor(s: &str) -> Result<f32, String> {
+    positive(parse_number(s.trim())?, s)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lengths() {
+        assert_eq!(length("0.3mm"), Ok(0.3));
+        assert_eq!(length("0.3"), Ok(0.3));
+        assert_eq!(length("300um"), Ok(0.3));
+        assert_eq!(length("5cm"), Ok(50.));
+        assert!(length("-1mm").is_err());
+        assert!(length("3parsec").is_err());
+    }
+
+    #[test]
+    fn times() {
+        assert_eq!(time("1/125"), Ok(0.008));
+        assert_eq!(time("2min"), Ok(120.));
+        assert_eq!(clock("20:15"), Ok(20.25));
+        assert!(clock("24:00").is_err());
+        assert_eq!(reading("0=9"), Ok((0., 9.)));
+        assert_eq!(reading("10min=-1.5"), Ok((600., -1.5)));
+        assert!(reading("10min").is_err());
+    }
+
+    #[test]
+    fn distances() {
+        assert_eq!(distance("inf"), Ok(f32::INFINITY));
+        assert_eq!(distance("500mm"), Ok(0.5));
+    }
+
+    #[test]
+    fn fstops() {
+        assert_eq!(fstop("f/22"), Ok(22.));
+        assert_eq!(fstop("16"), Ok(16.));
+    }
+}
@@ -0,0 +1,84 @@
+use egui::{Color32, RichText};
+use pinhole::design::Design;
+use pinhole::sweep::{Column, Value};
+
+const ROWS: [(Column, &str); 9] = [
+    (Column::FocalLength, "Focal length"),
+    (Column::Diameter, "Pinhole Ø"),
+    (Column::Projection, "Projection Ø"),
+    (Column::FNumber, "F-stop"),
+    (Column::ExposureFactor, "Exposure vs f/32"),
+    (Column::ViewAngle, "View angle"),
+    (Column::CoverageDiameter, "Coverage Ø"),
+    (Column::VignettingStops, "Vignetting"),
+    (Column::OptimalDiameter, "Optimal Ø"),
+];
+
+const HIGHER: Color32 = Color32::from_rgb(230, 150, 60);
+const LOWER: Color32 = Color32::from_rgb(90, 160, 230);
+
+// Designs side by side, values that differ from the first design coloured.
+pub fn comparison(ui: &mut egui::Ui, designs: &[Design]) {
+    let points: Vec<_> = designs.iter().map(Design::point).collect();
+    egui::Grid::new("comparison")
+        .striped(true)
+        .spacing([24., 6.])
+        .show(ui, |ui| {
+            ui.label("");
+            for d in designs {
+                ui.strong(if d.name.is_empty() {
+                    "(unnamed)"
+                } else {
+                    &d.name
+                });
+            }
+            ui.end_row();
+            ui.label("Format");
+            for p in &points {
+                ui.label(p.format.map_or("custom", |f| f.name));
+            }
+            ui.end_row();
+            for (column, label) in ROWS {
+                ui.label(label);
+                let reference = points.first().map(|p| number(column, p));
+                for p in &points {
+                    let x = number(column, p);
+                    let text = RichText::new(show(column, x));
+                    ui.label(match reference {
+                        Some(r) if differs(x, r) && x > r => text.color(HIGHER),
+                        Some(r) if differs(x, r) => text.color(LOWER),
+                        _ => text,
+                    });
+                }
+                ui.end_row();
+            }
+        });
+    ui.label(
+        RichText::new("Coloured values are higher or lower than in the first design")
+            .size(10.)
+            .color(Color32::GRAY),
+    );
+}
+
+fn show(column: Column, x: f32) -> String {
+    match column {
+        Column::FNumber => format!("f/{x:.0}"),
+        Column::ExposureFactor => format!("× {x:.1}"),
+        Column::ViewAngle => format!("{x:.1}˚"),
+        Column::VignettingStops => format!("{x:.1} stops"),
+        Column::Diameter | Column::OptimalDiameter => format!("{x:.2} mm"),
+        _ => format!("{x:.0} mm"),
+    }
+}
+
+fn number(column: Column, p: &pinhole::sweep::Point) -> f32 {
+    match column.value(p) {
+        Value::Number(x) => x,
+        Value::Text(_) => f32::NAN,
+    }
+}
+
+// More than half a percent apart.
+fn differs(a: f32, b: f32) -> bool {
+    (a - b).abs() > 0.005 * a.abs().max(b.abs())
+}
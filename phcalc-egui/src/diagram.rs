@@ -0,0 +1,88 @@
+use egui::{vec2, Align2, Color32, FontId, Rect, Sense, Stroke, StrokeKind};
+use phcalc_view::diagram::{Anchor, Diagram, Shape, Style};
+
+fn color(style: Style) -> Color32 {
+    let [r, g, b] = style.color();
+    Color32::from_rgb(r, g, b)
+}
+
+fn fill(style: Style) -> Color32 {
+    if style.filled() {
+        color(style)
+    } else {
+        Color32::TRANSPARENT
+    }
+}
+
+// A drawing as large as fits, keeping it to scale.
+pub fn paint(ui: &mut egui::Ui, diagram: &Diagram) {
+    let available = ui.available_size();
+    let scale = (available.x / diagram.width()).min(available.y.max(120.) / diagram.height());
+    let size = vec2(diagram.width(), diagram.height()) * scale;
+    let (response, painter) = ui.allocate_painter(size, Sense::hover());
+    // The light cone and the coverage circle can run far off the drawing.
+    let painter = painter.with_clip_rect(response.rect);
+    let origin = response.rect.left_top();
+    let at = |[x, y]: [f32; 2]| {
+        origin + vec2((x - diagram.min[0]) * scale, (diagram.max[1] - y) * scale)
+    };
+    painter.rect_filled(response.rect, 0., Color32::from_gray(30));
+    for shape in &diagram.shapes {
+        match shape {
+            Shape::Line { from, to, style } => {
+                let stroke = Stroke::new(style.width(), color(*style));
+                let points = [at(*from), at(*to)];
+                if style.dashed() {
+                    painter.extend(egui::Shape::dashed_line(&points, stroke, 6., 4.));
+                } else {
+                    painter.line_segment(points, stroke);
+                }
+            }
+            Shape::Rect { min, max, style } => {
+                let rect = Rect::from_two_pos(at(*min), at(*max));
+                let stroke = Stroke::new(style.width(), color(*style));
+                if style.dashed() {
+                    painter.rect_filled(rect, 0., fill(*style));
+                    let corners = [
+                        rect.left_top(),
+                        rect.right_top(),
+                        rect.right_bottom(),
+                        rect.left_bottom(),
+                        rect.left_top(),
+                    ];
+                    painter.extend(egui::Shape::dashed_line(&corners, stroke, 6., 4.));
+                } else {
+                    painter.rect(rect, 0., fill(*style), stroke, StrokeKind::Middle);
+                }
+            }
+            Shape::Circle {
+                centre,
+                radius,
+                style,
+            } => {
+                painter.circle(
+                    at(*centre),
+                    radius * scale,
+                    fill(*style),
+                    Stroke::new(style.width(), color(*style)),
+                );
+            }
+            Shape::Text {
+                at: pos,
+                text,
+                anchor,
+            } => {
+                painter.text(
+                    at(*pos),
+                    match anchor {
+                        Anchor::Start => Align2::LEFT_BOTTOM,
+                        Anchor::Middle => Align2::CENTER_BOTTOM,
+                    },
+                    text,
+                    FontId::proportional(12.),
+                    color(Style::Label),
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,227 @@
+use std::path::{Path, PathBuf};
+
+use egui::{Color32, RichText};
+use pinhole::design::Design;
+use pinhole::exposure::format_time;
+use pinhole::logbook::{self, Logbook, Shot};
+
+use crate::scenes;
+use crate::timer::{self, Finished};
+
+// What the main window has to do after the logbook was shown.
+pub enum Action {
+    Load(Design),
+    Status(String),
+}
+
+pub struct LogbookPanel {
+    pub book: Logbook,
+    // Why the file could not be read. Writing then could overwrite shots
+    // that are on disk but not in `book`.
+    pub read_only: Option<String>,
+    ev: Option<f32>,
+    actual: String,
+    location: String,
+    notes: String,
+    query: String,
+}
+
+impl Default for LogbookPanel {
+    fn default() -> Self {
+        Self {
+            book: Logbook::default(),
+            read_only: None,
+            ev: Some(12.),
+            actual: String::new(),
+            location: String::new(),
+            notes: String::new(),
+            query: String::new(),
+        }
+    }
+}
+
+impl LogbookPanel {
+    pub fn show(
+        &mut self,
+        ui: &mut egui::Ui,
+        path: Option<&Path>,
+        design: &Design,
+        last: Option<&Finished>,
+    ) -> Option<Action> {
+        let mut action = None;
+        if let Some(why) = &self.read_only {
+            ui.colored_label(
+                Color32::from_rgb(230, 90, 60),
+                format!("The logbook could not be read, nothing is written to it: {why}"),
+            );
+        }
+        if !self.book.unreadable.is_empty() {
+            let lines: Vec<_> = self
+                .book
+                .unreadable
+                .iter()
+                .map(|u| u.line.to_string())
+                .collect();
+            ui.colored_label(
+                Color32::from_rgb(230, 150, 60),
+                format!(
+                    "Skipped unreadable lines {}, they are kept in the file",
+                    lines.join(", ")
+                ),
+            )
+            .on_hover_text(
+                self.book
+                    .unreadable
+                    .iter()
+                    .map(|u| format!("line {}: {}", u.line, u.error))
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+            );
+        }
+        let writable = self.read_only.is_none();
+        ui.heading("New shot");
+        egui::Grid::new("new shot").num_columns(2).show(ui, |ui| {
+            ui.label("EV");
+            ui.horizontal(|ui| {
+                let mut metered = self.ev.is_some();
+                ui.checkbox(&mut metered, "");
+                match (metered, self.ev.as_mut()) {
+                    (true, Some(ev)) => scenes::ev_entry(ui, "shot scene", ev),
+                    (true, None) => self.ev = Some(12.),
+                    (false, _) => self.ev = None,
+                }
+                let expected = self
+                    .ev
+                    .and_then(|ev| Shot::expected(design, &design.film, ev));
+                if let Some(e) = expected {
+                    ui.label(format!("computed {}", format_time(e.corrected)));
+                }
+            });
+            ui.end_row();
+            ui.label("Actual");
+            ui.horizontal(|ui| {
+                ui.add(egui::TextEdit::singleline(&mut self.actual).hint_text("1/2, 30s"));
+                if let Some(f) = last {
+                    if ui
+                        .button(format!("From timer ({})", format_time(f.seconds as f32)))
+                        .clicked()
+                    {
+                        self.actual = format!("{:.1}", f.seconds);
+                    }
+                }
+            });
+            ui.end_row();
+            ui.label("Location");
+            ui.text_edit_singleline(&mut self.location);
+            ui.end_row();
+            ui.label("Notes");
+            ui.text_edit_singleline(&mut self.notes);
+            ui.end_row();
+        });
+        let actual = timer::parse_time(&self.actual).map(|t| t as f32);
+        let valid = self.actual.trim().is_empty() || actual.is_some();
+        if ui
+            .add_enabled(valid && writable, egui::Button::new("Log shot"))
+            .on_disabled_hover_text(if writable {
+                "The actual time is not a time"
+            } else {
+                "The logbook could not be read"
+            })
+            .clicked()
+        {
+            let shot = Shot {
+                taken: jiff::Zoned::now().strftime("%Y-%m-%d %H:%M").to_string(),
+                design: design.clone(),
+                film: design.film.clone(),
+                ev: self.ev,
+                exposure: self
+                    .ev
+                    .and_then(|ev| Shot::expected(design, &design.film, ev)),
+                actual,
+                location: self.location.clone(),
+                notes: std::mem::take(&mut self.notes),
+            };
+            action = Some(Action::Status(match path {
+                Some(path) => match self.book.append(path, shot) {
+                    Ok(()) => "Logged the shot".to_string(),
+                    Err(e) => format!("Could not write the logbook: {e}"),
+                },
+                None => "No place to store the logbook".to_string(),
+            }));
+            self.actual.clear();
+        }
+        ui.separator();
+        ui.horizontal(|ui| {
+            ui.label("Filter");
+            ui.text_edit_singleline(&mut self.query);
+            let shots = self.book.filter(&self.query);
+            ui.label(
+                RichText::new(format!("{} of {}", shots.len(), self.book.shots.len()))
+                    .color(Color32::GRAY),
+            );
+            if ui.button("CSV…").clicked() {
+                action = export(&shots, "csv", logbook::to_csv).or(action.take());
+            }
+            if ui.button("Markdown…").clicked() {
+                action = export(&shots, "md", logbook::to_markdown).or(action.take());
+            }
+        });
+        let mut remove = None;
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            egui::Grid::new("shots").striped(true).show(ui, |ui| {
+                for (i, shot) in self.book.shots.iter().enumerate().rev() {
+                    if !shot.matches(&self.query) {
+                        continue;
+                    }
+                    ui.label(&shot.taken);
+                    ui.label(&shot.design.name)
+                        .on_hover_text(format!("f/{:.0}", shot.design.point().fnumber()));
+                    ui.label(&shot.film);
+                    ui.label(shot.ev.map_or(String::new(), |ev| format!("EV {ev:.1}")));
+                    ui.label(
+                        shot.exposure
+                            .map_or(String::new(), |e| format_time(e.corrected)),
+                    );
+                    ui.label(shot.actual.map_or(String::new(), format_time));
+                    ui.label(&shot.location);
+                    ui.label(&shot.notes);
+                    if ui
+                        .small_button("Load")
+                        .on_hover_text("Back to the design of this shot")
+                        .clicked()
+                    {
+                        action = Some(Action::Load(shot.design.clone()));
+                    }
+                    if ui
+                        .add_enabled(writable, egui::Button::new("🗑").small())
+                        .on_hover_text("Remove")
+                        .clicked()
+                    {
+                        remove = Some(i);
+                    }
+                    ui.end_row();
+                }
+            });
+        });
+        if let Some(i) = remove {
+            self.book.shots.remove(i);
+            if let Some(path) = path {
+                if let Err(e) = self.book.save(path) {
+                    action = Some(Action::Status(format!("Could not write the logbook: {e}")));
+                }
+            }
+        }
+        action
+    }
+}
+
+fn export(shots: &[&Shot], extension: &str, to: fn(&[&Shot]) -> String) -> Option<Action> {
+    let path: PathBuf = rfd::FileDialog::new()
+        .add_filter(extension, &[extension])
+        .set_file_name(format!("logbook.{extension}"))
+        .save_file()?;
+    Some(Action::Status(match std::fs::write(&path, to(shots)) {
+        Ok(()) => format!("Exported {}", path.display()),
+        Err(e) => format!("Could not export {}: {e}", path.display()),
+    }))
+}
@@ -1,276 +1,566 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")] // hide console window on Windows in release
 
+use std::path::PathBuf;
+
 use egui::{Color32, RichText, Vec2};
+use phcalc_view::diagram::Diagram;
+use phcalc_view::{file, Input, Scale};
+use pinhole::design::Design;
+use pinhole::library::Library;
+use pinhole::sweep::Point;
+
+mod compare;
+mod diagram;
+mod logbook;
+mod plots;
+mod scenes;
+mod timer;
+
+const APP_ID: &str = "phcalc-egui";
 
 fn main() -> eframe::Result {
     env_logger::init(); // Log to stderr (if you run with `RUST_LOG=debug`).
     let options = eframe::NativeOptions {
-        viewport: egui::ViewportBuilder::default().with_inner_size([800.0, 600.0]),
+        viewport: egui::ViewportBuilder::default()
+            .with_inner_size([800.0, 600.0])
+            .with_app_id(APP_ID), // also names the storage with the saved session
         ..Default::default()
     };
     eframe::run_native(
         "Pinhole Calculations.",
         options,
-        Box::new(|_cc| Ok(Box::<MyApp>::default())),
+        Box::new(|cc| Ok(Box::new(MyApp::new(cc)))),
     )
 }
 
 struct MyApp {
-    ph_diameter: f32,
-    ph_thickness: f32,
-    ph_rayleighfactor: f32,
-    ph_focallength: f32,
-    ph_projradius: f32,
-    ph_wavelength: f32,
-    ph_magnification: f32,
-    ph_subjectdist: f32,
+    point: Point,
+    name: String,
+    film: String,
+    filters: Vec<String>,
+    notes: String,
+    path: Option<PathBuf>,
+    status: String,
+    zoom: f32,
+    library: Library,
+    // Why library.toml could not be read. Saving then would replace the
+    // designs in it with the ones stored since.
+    library_read_only: Option<String>,
+    show_library: bool,
+    show_compare: bool,
+    show_falloff: bool,
+    falloff_by_angle: bool,
+    show_sharpness: bool,
+    show_diagram: bool,
+    show_front: bool,
+    show_timer: bool,
+    timer: timer::TimerPanel,
+    show_logbook: bool,
+    logbook: logbook::LogbookPanel,
 }
 
 impl Default for MyApp {
     fn default() -> Self {
         Self {
-            ph_diameter: 0.3,
-            ph_thickness: 0.04,
-            ph_rayleighfactor: 1.56,
-            ph_focallength: 50.,
-            ph_projradius: 44.,
-            ph_wavelength: 550.,
-            ph_magnification: 0.,
-            ph_subjectdist: f32::INFINITY,
+            point: phcalc_view::defaults(),
+            name: String::new(),
+            film: String::new(),
+            filters: Vec::new(),
+            notes: String::new(),
+            path: None,
+            status: String::new(),
+            zoom: 1.4,
+            library: Library::default(),
+            library_read_only: None,
+            show_library: false,
+            show_compare: false,
+            show_falloff: false,
+            falloff_by_angle: false,
+            show_sharpness: false,
+            show_diagram: false,
+            show_front: false,
+            show_timer: false,
+            timer: timer::TimerPanel::default(),
+            show_logbook: false,
+            logbook: logbook::LogbookPanel::default(),
         }
     }
 }
 
+// As the JSON of a design file, so it comes back through the same version
+// check and migration an opened file does.
+const DESIGN_KEY: &str = "design";
+const PATH_KEY: &str = "path";
+const ZOOM_KEY: &str = "zoom";
+const LIBRARY_KEY: &str = "show_library";
+
+// The design library lives next to the saved session.
+fn library_path() -> Option<PathBuf> {
+    eframe::storage_dir(APP_ID).map(|dir| dir.join("library.toml"))
+}
+
+// So is the logbook, a line per shot.
+fn logbook_path() -> Option<PathBuf> {
+    eframe::storage_dir(APP_ID).map(|dir| dir.join("logbook.jsonl"))
+}
+
 impl MyApp {
-    fn calc_optimalsize(&self) -> f32 {
-        pinhole::calc_optimalsize(
-            self.ph_focallength,
-            self.ph_wavelength,
-            self.ph_rayleighfactor,
-            self.ph_magnification,
-        )
+    // Restore the last session. Anything that no longer fits the current
+    // layout is dropped and takes its default.
+    fn new(cc: &eframe::CreationContext<'_>) -> Self {
+        let mut app = Self::default();
+        if let Some(storage) = cc.storage {
+            if let Some(json) = storage.get_string(DESIGN_KEY) {
+                match Design::from_json(&json) {
+                    Ok(d) => app.set_design(d),
+                    Err(e) => app.status = format!("Could not restore the last design: {e}"),
+                }
+            }
+            app.path = eframe::get_value(storage, PATH_KEY);
+            app.zoom = eframe::get_value(storage, ZOOM_KEY).unwrap_or(app.zoom);
+            app.show_library = eframe::get_value(storage, LIBRARY_KEY).unwrap_or_default();
+        }
+        cc.egui_ctx.set_zoom_factor(app.zoom);
+        if let Some(path) = library_path() {
+            match Library::load(&path) {
+                Ok(library) => app.library = library,
+                Err(e) => {
+                    app.status = format!("Could not read the library: {e}");
+                    app.library_read_only = Some(e.to_string());
+                }
+            }
+        }
+        if let Some(path) = logbook_path() {
+            match pinhole::logbook::Logbook::load(&path) {
+                Ok(book) => app.logbook.book = book,
+                Err(e) => {
+                    app.status = format!("Could not read the logbook: {e}");
+                    app.logbook.read_only = Some(e.to_string());
+                }
+            }
+        }
+        app
+    }
+
+    // Back to the defaults for the design and window, the library, logbook
+    // and timer stay. An exposure that is running keeps its window.
+    fn reset(&mut self, ctx: &egui::Context) {
+        let timer = std::mem::take(&mut self.timer);
+        let show_timer = self.show_timer && timer.timer.is_running();
+        let library = std::mem::take(&mut self.library);
+        let library_read_only = self.library_read_only.take();
+        let book = std::mem::take(&mut self.logbook.book);
+        let read_only = self.logbook.read_only.take();
+        *self = Self {
+            library,
+            library_read_only,
+            show_timer,
+            timer,
+            ..Self::default()
+        };
+        self.logbook.book = book;
+        self.logbook.read_only = read_only;
+        ctx.set_zoom_factor(self.zoom);
+        self.status = "Reset to defaults".to_string();
+    }
+
+    fn save_library(&mut self) {
+        if self.library_read_only.is_some() {
+            return;
+        }
+        let Some(path) = library_path() else {
+            self.status = "No place to store the library".to_string();
+            return;
+        };
+        if let Err(e) = self.library.save(&path) {
+            self.status = format!("Could not save the library: {e}");
+        }
+    }
+
+    fn library_panel(&mut self, ui: &mut egui::Ui) {
+        ui.heading("Library");
+        if let Some(why) = &self.library_read_only {
+            ui.colored_label(
+                Color32::from_rgb(230, 90, 60),
+                format!("The library could not be read, nothing is written to it: {why}"),
+            );
+        }
+        let writable = self.library_read_only.is_none();
+        if ui
+            .add_enabled(
+                writable && !self.name.is_empty(),
+                egui::Button::new("Store current design"),
+            )
+            .on_disabled_hover_text(if writable {
+                "Give the design a name first"
+            } else {
+                "The library could not be read"
+            })
+            .clicked()
+        {
+            self.library.insert(self.design());
+            self.save_library();
+        }
+        ui.separator();
+        let mut load = None;
+        let mut remove = None;
+        for (i, d) in self.library.designs.iter().enumerate() {
+            ui.horizontal(|ui| {
+                if ui
+                    .selectable_label(d.name == self.name, &d.name)
+                    .on_hover_text("Load into the calculator")
+                    .clicked()
+                {
+                    load = Some(i);
+                }
+                if ui
+                    .add_enabled(writable, egui::Button::new("🗑").small())
+                    .on_hover_text("Remove")
+                    .clicked()
+                {
+                    remove = Some(i);
+                }
+            });
+        }
+        if let Some(i) = load {
+            self.set_design(self.library.designs[i].clone());
+            self.path = None;
+        }
+        if let Some(i) = remove {
+            self.library.remove(i);
+            self.save_library();
+        }
+        ui.separator();
+        ui.add_enabled(
+            !self.library.designs.is_empty(),
+            egui::Checkbox::new(&mut self.show_compare, "Compare"),
+        );
+    }
+
+    fn design(&self) -> Design {
+        let mut d = Design {
+            name: self.name.clone(),
+            notes: self.notes.clone(),
+            film: self.film.clone(),
+            filters: self.filters.clone(),
+            ..Default::default()
+        };
+        d.set_point(&self.point);
+        d
+    }
+
+    fn set_design(&mut self, d: Design) {
+        self.point = d.point();
+        self.name = d.name;
+        self.notes = d.notes;
+        self.film = d.film;
+        self.filters = d.filters;
+        let (_, unknown) = pinhole::filters::known(&self.filters);
+        if !unknown.is_empty() {
+            self.status = format!(
+                "Unknown filters {} are left out, the others are used",
+                unknown.join(", ")
+            );
+        }
+    }
+
+    fn open_design(&mut self) {
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter(file::FILTER_NAME, &file::FILTER_EXTENSIONS)
+            .pick_file()
+        else {
+            return;
+        };
+        match file::open(&path) {
+            Ok((d, status)) => {
+                self.status = status;
+                self.set_design(d);
+                self.path = Some(path);
+            }
+            Err(status) => self.status = status,
+        }
     }
 
-    fn calc_viewangle(&self) -> f32 {
-        pinhole::calc_viewangle(self.ph_diameter, self.ph_thickness)
+    fn save_design(&mut self, ask: bool) {
+        let design = self.design();
+        let path = match &self.path {
+            Some(path) if !ask => path.clone(),
+            _ => {
+                let Some(path) = rfd::FileDialog::new()
+                    .add_filter(file::FILTER_NAME, &file::FILTER_EXTENSIONS)
+                    .set_file_name(file::default_name(&design))
+                    .save_file()
+                else {
+                    return;
+                };
+                path
+            }
+        };
+        match file::save(&design, &path) {
+            Ok(status) => {
+                self.status = status;
+                self.path = Some(path);
+            }
+            Err(status) => self.status = status,
+        }
+    }
+
+    // Returns whether the window stays open. The diagram is only built
+    // while the window shows.
+    fn diagram_window(
+        &mut self,
+        ctx: &egui::Context,
+        title: &str,
+        open: bool,
+        build: fn(&Point) -> Diagram,
+    ) -> bool {
+        let mut open = open;
+        egui::Window::new(title)
+            .open(&mut open)
+            .default_size([480., 360.])
+            .show(ctx, |ui| {
+                let d = build(&self.point);
+                if ui.button("Export SVG…").clicked() {
+                    self.export_svg(&d);
+                }
+                diagram::paint(ui, &d);
+            });
+        open
+    }
+
+    fn export_svg(&mut self, d: &Diagram) {
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("SVG", &["svg"])
+            .set_file_name("diagram.svg")
+            .save_file()
+        else {
+            return;
+        };
+        self.status = match std::fs::write(&path, d.to_svg(800.)) {
+            Ok(()) => format!("Exported {}", path.display()),
+            Err(e) => format!("Could not export {}: {e}", path.display()),
+        };
     }
 
-    fn calc_vignetting(&self) -> (f32, f32) {
-        // projradius is a build parameter, set with slider.
-        pinhole::calc_vignetting(self.ph_focallength, self.ph_projradius / 2.)
+    // Filters picked from the menu are always in the database.
+    // A design file may name filters we do not know, those are left out and
+    // shown in red next to the menu.
+    fn stack(&self) -> pinhole::filters::Stack {
+        pinhole::filters::known(&self.filters).0
     }
 
-    fn calc_coverage_radius(&self) -> f32 {
-        pinhole::coverage_radius(self.ph_focallength, self.calc_viewangle())
+    fn filter_menu(&mut self, ui: &mut egui::Ui) {
+        ui.label("Filters");
+        let (stack, unknown) = pinhole::filters::known(&self.filters);
+        let text = if stack.is_empty() {
+            "None".to_string()
+        } else {
+            format!("{stack} ({:.1} stops)", stack.stops())
+        };
+        ui.menu_button(text, |ui| {
+            for f in pinhole::filters::FILTERS.iter() {
+                let mut on = self.filters.iter().any(|name| name == f.name);
+                if ui.checkbox(&mut on, f.name).changed() {
+                    if on {
+                        self.filters.push(f.name.to_string());
+                    } else {
+                        self.filters.retain(|name| name != f.name);
+                    }
+                }
+            }
+            if ui.button("Clear").clicked() {
+                self.filters.clear();
+                ui.close();
+            }
+        });
+        if !unknown.is_empty() {
+            ui.colored_label(
+                Color32::from_rgb(230, 90, 60),
+                format!("Unknown, left out: {}", unknown.join(", ")),
+            );
+        }
     }
 
-    fn diameter_to_filmsize(&self, diameter: f32) -> String {
-        match diameter {
-            x if (43.0..45.0).contains(&x) => "35mm".to_string(),
-            x if (74.0..76.0).contains(&x) => "645".to_string(),
-            x if (84.0..86.0).contains(&x) => "6x6".to_string(),
-            x if (92.0..94.0).contains(&x) => "6x7".to_string(),
-            x if (108.0..110.0).contains(&x) => "6x9".to_string(),
-            x if (134.0..136.0).contains(&x) => "6x12".to_string(),
-            x if (162.0..164.0).contains(&x) => "4\"x5\"".to_string(),
-            x if (180.0..182.0).contains(&x) => "6x17".to_string(),
-            x if (218.0..220.0).contains(&x) => "5\"x7\"".to_string(),
-            x if (325.0..327.0).contains(&x) => "8\"x10\"".to_string(),
-            x if (452.0..454.0).contains(&x) => "11\"x14\"".to_string(),
-            x if (650.0..652.0).contains(&x) => "16\"x20\"".to_string(),
-            x if (793.0..795.0).contains(&x) => "20\"x24\"".to_string(),
-            x => format!("{:.1} sq", ((x * x) / 2.0).sqrt()), // _ => "Custom".to_string(),
+    // The optimum for the light that gets through coloured filters.
+    fn filtered_optimum(&self, ui: &mut egui::Ui) {
+        let stack = self.stack();
+        let wavelength = stack.wavelength(self.point.wavelength);
+        if wavelength == self.point.wavelength {
+            return;
         }
+        let p = Point {
+            wavelength,
+            ..self.point
+        };
+        ui.label(format!(
+            "Through {stack} the light centres at {wavelength:.0} nm, optimal Ø {:.2} mm",
+            p.optimalsize()
+        ));
+    }
+
+    // One input as a slider, the projection with its formats next to it.
+    fn input_slider(&mut self, ui: &mut egui::Ui, input: Input, width: f32) {
+        let def = input.def();
+        let with_formats = input == Input::Projection;
+        ui.horizontal(|ui| {
+            ui.spacing_mut().slider_width = if with_formats { width - 84. } else { width };
+            let max = if def.infinite { f32::INFINITY } else { def.max };
+            let mut slider = egui::Slider::new(input.value_mut(&mut self.point), def.min..=max)
+                .logarithmic(def.scale == Scale::Log)
+                .fixed_decimals(def.decimals)
+                .text(def.label_with_unit());
+            if def.infinite {
+                slider = slider.largest_finite(def.max as f64);
+            }
+            ui.add(slider);
+            if with_formats {
+                egui::ComboBox::from_label("")
+                    .selected_text(phcalc_view::format_label(&self.point))
+                    .show_ui(ui, |ui| {
+                        for f in pinhole::formats::FORMATS.iter() {
+                            if ui
+                                .selectable_label(self.point.format == Some(f), f.name)
+                                .clicked()
+                            {
+                                phcalc_view::select_format(&mut self.point, f);
+                            }
+                        }
+                    });
+            }
+        });
+        phcalc_view::sync_format(&mut self.point);
     }
 }
 
 impl eframe::App for MyApp {
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        if let Ok(json) = self.design().to_json() {
+            storage.set_string(DESIGN_KEY, json);
+        }
+        eframe::set_value(storage, PATH_KEY, &self.path);
+        eframe::set_value(storage, ZOOM_KEY, &self.zoom);
+        eframe::set_value(storage, LIBRARY_KEY, &self.show_library);
+    }
+
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        if self.show_library {
+            egui::SidePanel::right("library").show(ctx, |ui| self.library_panel(ui));
+        }
+        let mut show_compare = self.show_compare && !self.library.designs.is_empty();
+        egui::Window::new("Compare designs")
+            .open(&mut show_compare)
+            .show(ctx, |ui| compare::comparison(ui, &self.library.designs));
+        self.show_compare = show_compare;
+        egui::Window::new("Vignetting falloff")
+            .open(&mut self.show_falloff)
+            .default_size([480., 320.])
+            .show(ctx, |ui| {
+                plots::falloff(ui, &self.point, &mut self.falloff_by_angle)
+            });
+        egui::Window::new("Sharpness")
+            .open(&mut self.show_sharpness)
+            .default_size([480., 320.])
+            .show(ctx, |ui| plots::sharpness(ui, &self.point));
+        self.show_diagram =
+            self.diagram_window(ctx, "Camera diagram", self.show_diagram, Diagram::section);
+        self.show_front = self.diagram_window(ctx, "Front view", self.show_front, Diagram::front);
+        self.timer.tick(ctx, &self.name, &self.film);
+        let stack = self.stack();
+        egui::Window::new("Exposure timer")
+            .open(&mut self.show_timer)
+            .show(ctx, |ui| {
+                self.timer.show(ui, &self.point, &self.film, &stack)
+            });
+        let design = self.design();
+        let mut action = None;
+        egui::Window::new("Logbook")
+            .open(&mut self.show_logbook)
+            .default_size([640., 400.])
+            .show(ctx, |ui| {
+                let path = logbook_path();
+                action = self
+                    .logbook
+                    .show(ui, path.as_deref(), &design, self.timer.last());
+            });
+        match action {
+            Some(logbook::Action::Load(d)) => {
+                self.status = format!("Loaded {} from the logbook", d.name);
+                self.set_design(d);
+            }
+            Some(logbook::Action::Status(s)) => self.status = s,
+            None => {}
+        }
         egui::CentralPanel::default().show(ctx, |ui| {
             let txt_width = 224.;
             //ctx.set_pixels_per_point(3.0);
-            ctx.set_zoom_factor(1.4);
+            self.zoom = ctx.zoom_factor();
             ui.style_mut().spacing.item_spacing = Vec2::new(4.0, 12.0);
             ui.style_mut().spacing.indent = 16.0;
             //
-            ui.heading("Pinhole Calculations");
-            ui.separator();
-            //
-            let max_width = ui.max_rect().width();
-            //
             ui.horizontal(|ui| {
-                ui.spacing_mut().slider_width = max_width - txt_width;
-                ui.add(
-                    egui::Slider::new(&mut self.ph_diameter, 0.01..=2.)
-                        .drag_value_speed(0.001)
-                        .min_decimals(3)
-                        //.custom_formatter(|n, _| format!("{:.3} / {:.0}", n, n * 1000.))
-                        //.text("Pinhole Ø (mm/micron)"),
-                        .text("Pinhole Ø (mm)"),
-                );
+                ui.heading("Pinhole Calculations");
+                if ui.button("Open…").clicked() {
+                    self.open_design();
+                }
+                if ui.button("Save").clicked() {
+                    self.save_design(false);
+                }
+                if ui.button("Save As…").clicked() {
+                    self.save_design(true);
+                }
+                if ui.button("Reset to defaults").clicked() {
+                    self.reset(ctx);
+                }
+                ui.toggle_value(&mut self.show_library, "Library");
+                ui.toggle_value(&mut self.show_falloff, "Falloff");
+                ui.toggle_value(&mut self.show_sharpness, "Sharpness");
+                ui.toggle_value(&mut self.show_diagram, "Diagram");
+                ui.toggle_value(&mut self.show_front, "Front view");
+                ui.toggle_value(&mut self.show_timer, "Timer");
+                ui.toggle_value(&mut self.show_logbook, "Logbook");
+                ui.label(RichText::new(&self.status).color(Color32::GRAY));
             });
             ui.horizontal(|ui| {
-                ui.spacing_mut().slider_width = max_width - txt_width;
-                ui.add(
-                    egui::Slider::new(&mut self.ph_thickness, 0.01..=1.)
-                        .drag_value_speed(0.001)
-                        .text("Pinhole thickness (mm)"),
-                );
+                ui.label("Name");
+                ui.text_edit_singleline(&mut self.name);
+                ui.label("Film");
+                ui.text_edit_singleline(&mut self.film);
+                ui.menu_button("▾", |ui| {
+                    for f in pinhole::film::FILMS.iter() {
+                        if ui.button(f.name).clicked() {
+                            self.film = f.name.to_string();
+                            ui.close();
+                        }
+                    }
+                })
+                .response
+                .on_hover_text("Films with reciprocity data");
+                self.filter_menu(ui);
             });
             ui.horizontal(|ui| {
-                ui.spacing_mut().slider_width = max_width - txt_width;
+                ui.label("Notes");
                 ui.add(
-                    egui::Slider::new(&mut self.ph_focallength, 1.0..=10000.)
-                        .logarithmic(true)
-                        .drag_value_speed(1.)
-                        .text("Focal length (mm)"),
+                    egui::TextEdit::multiline(&mut self.notes)
+                        .desired_rows(1)
+                        .desired_width(f32::INFINITY),
                 );
             });
-            ui.horizontal(|ui| {
-                ui.spacing_mut().slider_width = max_width - (txt_width + 84.);
-                ui.add(
-                    egui::Slider::new(&mut self.ph_projradius, 10.0..=1000.)
-                        .fixed_decimals(0)
-                        .drag_value_speed(1.)
-                        .text("Desired projection Ø (mm)"),
-                );
-                //});
-                egui::ComboBox::from_label("")
-                    //.selected_text(format!("{:.0}", self.ph_projradius))
-                    .selected_text(self.diameter_to_filmsize(self.ph_projradius))
-                    .show_ui(ui, |ui| {
-                        ui.selectable_value(
-                            &mut self.ph_projradius,
-                            pinhole::projection_diameter(24., 36.),
-                            "35mm",
-                        ); // is diameter!
-                        ui.selectable_value(
-                            &mut self.ph_projradius,
-                            pinhole::projection_diameter(60., 45.),
-                            "645",
-                        );
-                        ui.selectable_value(
-                            &mut self.ph_projradius,
-                            pinhole::projection_diameter(60., 60.),
-                            "6x6",
-                        );
-                        ui.selectable_value(
-                            &mut self.ph_projradius,
-                            pinhole::projection_diameter(60., 70.),
-                            "6x7",
-                        );
-                        ui.selectable_value(
-                            &mut self.ph_projradius,
-                            pinhole::projection_diameter(60., 90.),
-                            "6x9",
-                        );
-                        ui.selectable_value(
-                            &mut self.ph_projradius,
-                            pinhole::projection_diameter(60., 120.),
-                            "6x12",
-                        );
-                        ui.selectable_value(
-                            &mut self.ph_projradius,
-                            pinhole::projection_diameter(4. * 25.4, 5. * 25.4),
-                            "4\"x5\"",
-                        );
-                        ui.selectable_value(
-                            &mut self.ph_projradius,
-                            pinhole::projection_diameter(60., 170.),
-                            "6x17",
-                        );
-                        ui.selectable_value(
-                            &mut self.ph_projradius,
-                            pinhole::projection_diameter(5. * 25.4, 7. * 25.4),
-                            "5\"x7\"",
-                        );
-                        ui.selectable_value(
-                            &mut self.ph_projradius,
-                            pinhole::projection_diameter(8. * 25.4, 10. * 25.4),
-                            "8\"x10\"",
-                        );
-                        ui.selectable_value(
-                            &mut self.ph_projradius,
-                            pinhole::projection_diameter(11. * 25.4, 14. * 25.4),
-                            "11\"x14\"",
-                        );
-                        ui.selectable_value(
-                            &mut self.ph_projradius,
-                            pinhole::projection_diameter(16. * 25.4, 20. * 25.4),
-                            "16\"x20\"",
-                        );
-                        ui.selectable_value(
-                            &mut self.ph_projradius,
-                            pinhole::projection_diameter(20. * 25.4, 24. * 25.4),
-                            "20\"x24\"",
-                        );
-                        // 5"x7"/219, 8"x10"/326, 11"x14", 16"x20", 20"x24"
-                        //ui.selectable_value(&mut self.ph_projradius, 93., "6x7");
-                    });
-            });
-            ui.label(format!(
-                "View angle is {:.1}˚ which covers a diameter of {:.1}mm",
-                2. * self.calc_viewangle(),
-                2. * self.calc_coverage_radius(),
-            ));
-            ui.label(format!(
-                "F-stop is f/{:.1} which is {:.1} f-stops from f/32 (t · {:.1})",
-                self.ph_focallength / self.ph_diameter,
-                pinhole::delta_thirds(32f32, self.ph_focallength / self.ph_diameter),
-                (self.ph_focallength / self.ph_diameter / 32.)
-                    * (self.ph_focallength / self.ph_diameter / 32.)
-            ));
-            ////
-            /*
-            ui.horizontal(|ui| {
-                ui.label(RichText::new("F-stop"));
-                ui.label(RichText::new("167").color(Color32::RED));
-                ui.label("is");
-                ui.label(RichText::new("12").color(Color32::BLUE));
-                ui.label("f-stops from f/32");
-            });
-            */
-            ////
-            ui.label(format!(
-                "Vignetting for desired projection Ø is {:.1} f-stops ({:.2}) at a {:.1}˚ angle",
-                //self.ph_diagonal * (90. - self.ph_viewangle).to_radians().tan()
-                pinhole::stop_equivalent(self.calc_vignetting().0),
-                self.calc_vignetting().0,
-                self.calc_vignetting().1
-            ));
-            //
             ui.separator();
-            ui.horizontal(|ui| {
-                ui.spacing_mut().slider_width = max_width - txt_width;
-                ui.add(
-                    egui::Slider::new(&mut self.ph_wavelength, 350.0..=700.)
-                        .text("Wavelength (nm)"),
-                );
-            });
-            ui.horizontal(|ui| {
-                ui.spacing_mut().slider_width = max_width - txt_width;
-                ui.add(
-                    egui::Slider::new(&mut self.ph_rayleighfactor, 1.0..=2.0)
-                        .fixed_decimals(2)
-                        .text("Rayleigh factor"),
-                );
-            });
-            ui.horizontal(|ui| {
-                ui.spacing_mut().slider_width = max_width - txt_width;
-                ui.add(
-                    egui::Slider::new(&mut self.ph_subjectdist, 0.01..=f32::INFINITY) //  10000.)
-                        .logarithmic(true)
-                        .largest_finite(1000.)
-                        .text("Subject distance (m)"),
-                );
-            });
-            self.ph_magnification = self.ph_focallength / (self.ph_subjectdist * 1000.);
-            ui.label(format!(
-                "Optimal pinhole Ø for this focal length is {:.2} mm (at {:.1} magnification)",
-                self.calc_optimalsize(),
-                self.ph_magnification
-            ));
-            //
+            let max_width = ui.max_rect().width();
+            for (i, section) in phcalc_view::SECTIONS.iter().enumerate() {
+                if i > 0 {
+                    ui.separator();
+                }
+                for &input in section.inputs {
+                    self.input_slider(ui, input, max_width - txt_width);
+                }
+                for output in section.outputs.iter().filter(|o| o.applies(&self.point)) {
+                    ui.label(output.line(&self.point).to_string());
+                    if *output == phcalc_view::Output::OptimalSize {
+                        self.filtered_optimum(ui);
+                    }
+                }
+            }
             ui.separator();
             ui.label(
                 RichText::new("(c) Peter Berck 2025")
@@ -280,3 +570,27 @@ impl eframe::App for MyApp {
         });
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use phcalc_view::timer::State;
+
+    #[test]
+    fn reset_keeps_a_running_exposure() {
+        let ctx = egui::Context::default();
+        let mut app = MyApp {
+            name: "Zero".to_string(),
+            show_timer: true,
+            ..MyApp::default()
+        };
+        app.timer.timer.total = 60.;
+        app.timer.timer.start(5.);
+        app.reset(&ctx);
+        assert_eq!(app.name, "");
+        assert!(app.show_timer);
+        assert_eq!(app.timer.timer.state, State::Running { end: 65. });
+        // Done when it comes to its end after the reset, for the logbook.
+        assert_eq!(app.timer.timer.tick(65.), Some(60.));
+    }
+}
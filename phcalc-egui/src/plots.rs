@@ -0,0 +1,123 @@
+use egui::Color32;
+use egui_plot::{Legend, Line, LineStyle, Plot, PlotPoints, Polygon, VLine};
+use pinhole::falloff::{self, Falloff};
+use pinhole::sharpness::{self, Blur};
+use pinhole::sweep::Point;
+
+const BLUE: Color32 = Color32::from_rgb(90, 160, 230);
+const ORANGE: Color32 = Color32::from_rgb(230, 150, 60);
+const GREEN: Color32 = Color32::from_rgb(120, 200, 120);
+const MARKER: Color32 = Color32::GRAY;
+const RED: Color32 = Color32::from_rgb(220, 80, 80);
+const TOLERANCE: Color32 = Color32::from_rgba_premultiplied(40, 60, 40, 40);
+
+// Relative illumination from the centre of the image outwards, against the
+// radius on the film or the field angle.
+pub fn falloff(ui: &mut egui::Ui, p: &Point, by_angle: &mut bool) {
+    ui.horizontal(|ui| {
+        ui.label("Against");
+        ui.selectable_value(by_angle, false, "image radius");
+        ui.selectable_value(by_angle, true, "field angle");
+    });
+    let by_angle = *by_angle;
+    let x = move |f: &Falloff| if by_angle { f.angle } else { f.radius } as f64;
+    let markers: Vec<(&str, f32)> = match p.format {
+        Some(format) => ["Half short side", "Half long side", "Corner"]
+            .into_iter()
+            .zip(format.radii())
+            .collect(),
+        None => vec![("Projection edge", p.projection / 2.)],
+    };
+    let far = markers
+        .iter()
+        .map(|m| m.1)
+        .fold(p.projection / 2., f32::max);
+    let curve = falloff::curve(p, 1.25 * far, 200);
+    let series = |y: fn(&Falloff) -> f32| -> PlotPoints {
+        curve.iter().map(|f| [x(f), 100. * y(f) as f64]).collect()
+    };
+    Plot::new("falloff")
+        .legend(Legend::default())
+        .x_axis_label(if by_angle {
+            "Field angle (˚)"
+        } else {
+            "Image radius (mm)"
+        })
+        .y_axis_label("Illumination (%)")
+        .include_y(0.)
+        .include_y(100.)
+        .label_formatter(move |name, v| {
+            let stops = pinhole::stop_equivalent(v.y as f32 / 100.);
+            let at = if by_angle {
+                format!("{:.1}˚", v.x)
+            } else {
+                format!("{:.1} mm", v.x)
+            };
+            format!("{name}\n{at}\n{:.0} % ({stops:.1} stops)", v.y)
+        })
+        .show(ui, |plot| {
+            plot.line(Line::new("cos⁴", series(|f| f.cos4)).color(BLUE));
+            plot.line(Line::new("Plate thickness", series(|f| f.thickness)).color(ORANGE));
+            plot.line(
+                Line::new("Combined", series(Falloff::total))
+                    .color(GREEN)
+                    .width(2.)
+                    .fill(0.),
+            );
+            for (name, radius) in markers {
+                plot.vline(
+                    VLine::new(name, x(&falloff::at_radius(p, radius)))
+                        .color(MARKER)
+                        .style(LineStyle::dashed_loose()),
+                );
+            }
+        });
+}
+
+// Blur on the film against the pinhole diameter, with the diameters that are
+// within 10% of the sharpest shaded.
+pub fn sharpness(ui: &mut egui::Ui, p: &Point) {
+    let optimum = p.optimalsize();
+    let from = (optimum / 3.).min(0.8 * p.diameter);
+    let to = (optimum * 3.).max(1.2 * p.diameter);
+    let curve = sharpness::curve(p, from, to, 200);
+    let series = |y: fn(&Blur) -> f32| -> PlotPoints {
+        curve
+            .iter()
+            .map(|b| [b.diameter as f64, y(b) as f64])
+            .collect()
+    };
+    let (lo, hi) = sharpness::tolerance(p, 0.1);
+    let top = 1.1 * curve.iter().map(Blur::total).fold(0., f32::max);
+    let band = [[lo, 0.], [hi, 0.], [hi, top], [lo, top]].map(|[x, y]| [x as f64, y as f64]);
+    Plot::new("sharpness")
+        .legend(Legend::default())
+        .x_axis_label("Pinhole Ø (mm)")
+        .y_axis_label("Blur Ø on the film (mm)")
+        .include_y(0.)
+        .label_formatter(|name, v| format!("{name}\nØ {:.3} mm\nblur {:.3} mm", v.x, v.y))
+        .show(ui, |plot| {
+            plot.polygon(
+                Polygon::new("Within 10%", band.to_vec())
+                    .fill_color(TOLERANCE)
+                    .width(0.),
+            );
+            plot.line(Line::new("Geometric", series(|b| b.geometric)).color(BLUE));
+            plot.line(Line::new("Diffraction", series(|b| b.diffraction)).color(ORANGE));
+            plot.line(
+                Line::new("Total", series(Blur::total))
+                    .color(GREEN)
+                    .width(2.),
+            );
+            plot.vline(
+                VLine::new("Optimal", optimum)
+                    .color(MARKER)
+                    .style(LineStyle::dashed_loose()),
+            );
+            plot.vline(VLine::new("Current", p.diameter).color(RED));
+        });
+    ui.label(format!(
+        "Within 10% of the sharpest from {lo:.3} to {hi:.3} mm, the current pinhole gives {:.3} mm blur",
+        sharpness::blur(p).total()
+    ));
+}
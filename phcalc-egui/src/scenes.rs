@@ -0,0 +1,14 @@
+use pinhole::scenes::{self, SCENES};
+
+// An EV typed in, or picked from the scene table next to it.
+pub fn ev_entry(ui: &mut egui::Ui, id: &str, ev: &mut f32) {
+    ui.add(egui::DragValue::new(ev).range(-6.0..=20.).speed(0.1))
+        .on_hover_text("EV at ISO 100");
+    egui::ComboBox::from_id_salt(id)
+        .selected_text(scenes::nearest(*ev).name)
+        .show_ui(ui, |ui| {
+            for s in SCENES.iter() {
+                ui.selectable_value(ev, s.ev, format!("{} (EV {})", s.name, s.ev));
+            }
+        });
+}
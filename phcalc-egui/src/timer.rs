@@ -0,0 +1,220 @@
+use egui::{Color32, RichText};
+use phcalc_view::timer::{State, Timer};
+use pinhole::exposure::{self, format_clock, format_time};
+use pinhole::film;
+use pinhole::filters::Stack;
+use pinhole::stops::{self, Division};
+use pinhole::sweep::Point;
+
+// An exposure that ran to the end.
+pub struct Finished {
+    pub at: String,
+    pub name: String,
+    pub film: String,
+    pub seconds: f64,
+}
+
+pub struct TimerPanel {
+    metered: f32,
+    ev: f32,
+    at: f32,
+    pub timer: Timer,
+    log: Vec<Finished>,
+}
+
+impl Default for TimerPanel {
+    fn default() -> Self {
+        Self {
+            metered: 1. / 125.,
+            ev: 15.,
+            at: 16.,
+            timer: Timer::new(0.),
+            log: Vec::new(),
+        }
+    }
+}
+
+const PAUSE_STOPS: [(f32, &str); 4] = [
+    (f32::INFINITY, "Shutter closed"),
+    (1., "1 stop less light"),
+    (2., "2 stops less light"),
+    (3., "3 stops less light"),
+];
+
+// "1/125", "2" or "30s" in seconds.
+pub fn parse_time(s: &str) -> Option<f64> {
+    let s = s.trim().trim_end_matches('s').trim();
+    match s.split_once('/') {
+        Some((n, d)) => Some(n.trim().parse::<f64>().ok()? / d.trim().parse::<f64>().ok()?),
+        None => s.parse().ok(),
+    }
+}
+
+impl TimerPanel {
+    // The exposure that finished last, for the logbook.
+    pub fn last(&self) -> Option<&Finished> {
+        self.log.last()
+    }
+
+    // Every frame, with the window open or not, so an exposure finishes on
+    // time. Logged with the moment it ended, not when this noticed.
+    pub fn tick(&mut self, ctx: &egui::Context, name: &str, film_name: &str) {
+        let now = ctx.input(|i| i.time);
+        let end = match self.timer.state {
+            State::Running { end } => end,
+            _ => now,
+        };
+        if let Some(seconds) = self.timer.tick(now) {
+            let late = jiff::SignedDuration::from_secs_f64((now - end).max(0.));
+            let wall = jiff::Zoned::now();
+            let ended = wall.checked_sub(late).unwrap_or(wall);
+            self.log.push(Finished {
+                at: ended.strftime("%Y-%m-%d %H:%M:%S").to_string(),
+                name: name.to_string(),
+                film: film::find(film_name)
+                    .map_or(film_name, |f| f.name)
+                    .to_string(),
+                seconds,
+            });
+        }
+        if self.timer.is_running() {
+            ctx.request_repaint_after(std::time::Duration::from_millis(200));
+        }
+    }
+
+    // Metered without the filters, timed through them.
+    pub fn show(&mut self, ui: &mut egui::Ui, p: &Point, film_name: &str, stack: &Stack) {
+        let now = ui.input(|i| i.time);
+        let film = film::find(film_name);
+        ui.horizontal(|ui| {
+            ui.label("Metered");
+            ui.add(
+                egui::Slider::new(&mut self.metered, 1. / 4000.0..=60.)
+                    .logarithmic(true)
+                    .custom_formatter(|v, _| format_time(v as f32))
+                    .custom_parser(parse_time),
+            );
+            egui::ComboBox::from_id_salt("metered at")
+                .selected_text(format!("at f/{}", self.at))
+                .show_ui(ui, |ui| {
+                    for s in stops::series(Division::Third, 64.).iter().skip(3) {
+                        ui.selectable_value(&mut self.at, s.marked(), s.to_string());
+                    }
+                });
+        });
+        ui.horizontal(|ui| {
+            ui.label("No meter, EV");
+            crate::scenes::ev_entry(ui, "timer scene", &mut self.ev);
+            let iso = film.map_or(100., |f| f.iso);
+            if ui
+                .button("Use")
+                .on_hover_text(format!("As the metered time at ISO {iso}"))
+                .clicked()
+            {
+                self.metered = exposure::metered_time(self.ev, iso, self.at);
+            }
+        });
+        let e = exposure::at_pinhole(stack.time(self.metered), self.at, p.fnumber(), film);
+        if !stack.is_empty() {
+            ui.label(format!(
+                "Through {stack}: exposure × {:.0} ({:.1} stops)",
+                stack.factor(),
+                stack.stops()
+            ));
+        }
+        ui.label(match film {
+            Some(f) => format!(
+                "{} at f/{:.0}, {} with {}'s reciprocity",
+                format_time(e.metered),
+                p.fnumber(),
+                format_time(e.corrected),
+                f.name
+            ),
+            None => format!(
+                "{} at f/{:.0}, no reciprocity data for the film",
+                format_time(e.metered),
+                p.fnumber()
+            ),
+        });
+        if !self.timer.is_running() {
+            self.timer.total = e.corrected as f64;
+        }
+        let remaining = self.timer.remaining(now);
+        let colour = match self.timer.state {
+            State::Paused { .. } => Color32::from_rgb(230, 150, 60),
+            State::Done => Color32::from_rgb(90, 200, 90),
+            _ => ui.visuals().strong_text_color(),
+        };
+        ui.label(
+            RichText::new(format_clock(remaining as f32))
+                .size(64.)
+                .monospace()
+                .color(colour),
+        );
+        ui.horizontal(|ui| {
+            match self.timer.state {
+                State::Idle | State::Done => {
+                    if ui.button("Start").clicked() {
+                        self.timer.start(now);
+                    }
+                }
+                State::Running { .. } => {
+                    if ui.button("Pause").clicked() {
+                        self.timer.pause(now);
+                    }
+                }
+                State::Paused { .. } => {
+                    if ui.button("Resume").clicked() {
+                        self.timer.resume(now);
+                    }
+                }
+            }
+            if ui
+                .add_enabled(self.timer.is_running(), egui::Button::new("Stop"))
+                .clicked()
+            {
+                self.timer.stop();
+            }
+            egui::ComboBox::from_id_salt("pause")
+                .selected_text(
+                    PAUSE_STOPS
+                        .iter()
+                        .find(|(s, _)| *s == self.timer.pause_stops)
+                        .map_or("", |(_, label)| label),
+                )
+                .show_ui(ui, |ui| {
+                    for (stops, label) in PAUSE_STOPS {
+                        ui.selectable_value(&mut self.timer.pause_stops, stops, label);
+                    }
+                })
+                .response
+                .on_hover_text("What a pause stands for, the rest of the exposure grows to match");
+        });
+        if !self.log.is_empty() {
+            ui.separator();
+            egui::Grid::new("exposure log")
+                .striped(true)
+                .show(ui, |ui| {
+                    for f in self.log.iter().rev() {
+                        ui.label(&f.at);
+                        ui.label(&f.name);
+                        ui.label(&f.film);
+                        ui.label(format_clock(f.seconds as f32));
+                        ui.end_row();
+                    }
+                });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shutter_times() {
+        assert_eq!(parse_time("1/125"), Some(0.008));
+        assert_eq!(parse_time(" 30s "), Some(30.));
+        assert_eq!(parse_time("x"), None);
+    }
+}
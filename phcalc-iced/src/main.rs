@@ -1,43 +1,42 @@
+use std::path::PathBuf;
+
 use iced::{
-    widget::{column, horizontal_rule, row, slider, text},
-    window, Element, Length, Task,
+    Element, Length, Task,
+    widget::{button, column, horizontal_rule, pick_list, row, slider, text, text_input},
+    window,
 };
+use phcalc_view::{Input, file};
+use pinhole::design::Design;
+use pinhole::formats::{FORMATS, Format};
+use pinhole::sweep::Point;
 
 #[derive(Debug, Default)]
 struct AppState {
-    ph_diameter: f32,
-    ph_thickness: f32,
-    ph_viewangle: f32,
-    ph_diagonal: f32,
-    ph_focallength: f32,
-    ph_wavelength: f32,
-    ph_rayleighfactor: f32,
+    // Last opened design, keeps what this window does not show.
+    design: Design,
+    path: Option<PathBuf>,
+    status: String,
+    name: String,
+    film: String,
+    point: Point,
 }
 
 #[derive(Debug, Clone)]
 enum Message {
-    UpdatePhDiameter(f32),
-    UpdatePhThickness(f32),
-    UpdatePhDiagonal(f32),
-    UpdatePhWavelength(f32),
-    UpdatePhRayleighFactor(f32),
-    UpdatePhFocallength(f32),
+    Open,
+    Save,
+    SaveAs,
+    UpdateName(String),
+    UpdateFilm(String),
+    // Slider position, see InputDef::from_slider.
+    UpdateInput(Input, f32),
+    SelectFormat(Format),
 }
 
 impl AppState {
     fn new() -> (Self, Task<Message>) {
-        let mut state = Self {
-            ph_diameter: 0.30,
-            ph_thickness: 0.05,
-            ph_viewangle: 0.0,
-            ph_diagonal: 42.0,
-            ph_focallength: 50.0,
-            ph_wavelength: 550.,
-            ph_rayleighfactor: 1.56,
-        };
-
-        state.ph_viewangle = state.calc_viewangle();
-
+        let mut state = Self::default();
+        state.set_design(Design::default());
         (state, Task::none())
     }
 
@@ -45,160 +44,143 @@ impl AppState {
         window::close(id)
     }
 
-    // Note that this is half the total view angle.
-    fn calc_viewangle(&self) -> f32 {
-        let div = self.ph_diameter / self.ph_thickness;
-        let viewangle: f32 = div.atan();
-        viewangle.to_degrees()
+    fn design(&self) -> Design {
+        let mut d = Design {
+            name: self.name.clone(),
+            film: self.film.clone(),
+            ..self.design.clone()
+        };
+        d.set_point(&self.point);
+        d
     }
 
-    fn calc_optimalsize(&self) -> f32 {
-        self.ph_rayleighfactor * (self.ph_wavelength * self.ph_focallength / 1000000.).sqrt()
+    fn set_design(&mut self, d: Design) {
+        self.name = d.name.clone();
+        self.film = d.film.clone();
+        self.point = d.point();
+        self.design = d;
     }
 
-    // from https://cral-perso.univ-lyon1.fr/labo/fc/cdroms/cdrom2004/cd_venus/documents/pinhole/pinhole_imaging.html
-    fn calc_vignetting(&self) -> (f32, f32) {
-        // diagonal is radius of projected circle
-        let div = self.ph_focallength / self.ph_diagonal;
-        let angle = 90. - div.atan().to_degrees();
-        let cos4 = angle.to_radians().cos(); //.to_degrees();
-        let cos4 = cos4.powi(4);
-        (cos4, angle)
+    fn open(&mut self) {
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter(file::FILTER_NAME, &file::FILTER_EXTENSIONS)
+            .pick_file()
+        else {
+            return;
+        };
+        match file::open(&path) {
+            Ok((d, status)) => {
+                self.set_design(d);
+                self.status = status;
+                self.path = Some(path);
+            }
+            Err(status) => self.status = status,
+        }
     }
 
-    fn update(&mut self, message: Message) {
-        match message {
-            Message::UpdatePhDiameter(v) => {
-                self.ph_diameter = v;
-                self.ph_viewangle = self.calc_viewangle();
+    fn save(&mut self, ask: bool) {
+        let design = self.design();
+        let path = match &self.path {
+            Some(path) if !ask => path.clone(),
+            _ => {
+                let Some(path) = rfd::FileDialog::new()
+                    .add_filter(file::FILTER_NAME, &file::FILTER_EXTENSIONS)
+                    .set_file_name(file::default_name(&design))
+                    .save_file()
+                else {
+                    return;
+                };
+                path
             }
-            Message::UpdatePhThickness(v) => {
-                self.ph_thickness = v;
-                self.ph_viewangle = self.calc_viewangle();
+        };
+        match file::save(&design, &path) {
+            Ok(status) => {
+                self.status = status;
+                self.path = Some(path);
             }
-            Message::UpdatePhDiagonal(v) => {
-                self.ph_diagonal = v;
+            Err(status) => self.status = status,
+        }
+    }
+
+    fn update(&mut self, message: Message) {
+        match message {
+            Message::Open => self.open(),
+            Message::Save => self.save(false),
+            Message::SaveAs => self.save(true),
+            Message::UpdateName(v) => {
+                self.name = v;
             }
-            Message::UpdatePhWavelength(v) => {
-                self.ph_wavelength = v;
+            Message::UpdateFilm(v) => {
+                self.film = v;
             }
-            Message::UpdatePhRayleighFactor(v) => {
-                self.ph_rayleighfactor = v;
+            Message::UpdateInput(input, v) => {
+                input.set(&mut self.point, input.def().from_slider(v));
             }
-            Message::UpdatePhFocallength(v) => {
-                self.ph_focallength = v;
+            Message::SelectFormat(f) => {
+                if let Some(f) = FORMATS.iter().find(|x| **x == f) {
+                    phcalc_view::select_format(&mut self.point, f);
+                }
             }
         }
     }
 
+    fn input_row(&self, input: Input) -> Element<'_, Message> {
+        let def = input.def();
+        let v = input.value(&self.point);
+        let label =
+            text(format!("{} {}  ", def.label, def.format_value(v))).width(Length::FillPortion(1));
+        let slider = slider(def.slider_range(), def.to_slider(v), move |s| {
+            Message::UpdateInput(input, s)
+        })
+        .step(def.slider_step());
+        let row = if input == Input::Projection {
+            row![
+                label,
+                slider.width(Length::FillPortion(3)),
+                pick_list(
+                    &FORMATS[..],
+                    self.point.format.copied(),
+                    Message::SelectFormat
+                )
+                .placeholder("Custom")
+                .width(Length::FillPortion(1)),
+            ]
+        } else {
+            row![label, slider.width(Length::FillPortion(4))]
+        };
+        row.spacing(8).padding(8).into()
+    }
+
     fn view(&self) -> Element<'_, Message> {
-        column![
+        let mut page = column![
             text("Pinhole Calculations").size(32),
-            horizontal_rule(48),
-            column![
-                // Label for this pane.
-                text("View angle").size(24),
-                // Value and slider.
-                row![
-                    text(format!("diameter {:.2} mm  ", self.ph_diameter))
-                        .width(Length::FillPortion(1)),
-                    slider(0.01..=1.00, self.ph_diameter, |v| {
-                        Message::UpdatePhDiameter(v)
-                    })
-                    .step(0.01)
-                    .width(Length::FillPortion(4)),
-                ]
-                .padding(8),
-                row![
-                    text(format!("thickness {:.2} mm  ", self.ph_thickness))
-                        .width(Length::FillPortion(1)),
-                    slider(0.01..=1.00, self.ph_thickness, |v| {
-                        Message::UpdatePhThickness(v)
-                    })
-                    .step(0.01)
-                    .width(Length::FillPortion(4)),
-                ]
-                .padding(8),
-                text(format!("View angle {:.0} degrees", 2. * self.ph_viewangle)),
-                // Calculated value.
-                row![
-                    text(format!("film radius {:.0} mm  ", self.ph_diagonal))
-                        .width(Length::FillPortion(1)),
-                    slider(10.0..=200., self.ph_diagonal, |v| {
-                        Message::UpdatePhDiagonal(v)
-                    })
-                    .step(1.)
-                    .width(Length::FillPortion(4)),
-                ]
-                .padding(8),
-                text(format!(
-                    "Focal length needed to cover radius is {:.0} mm",
-                    self.ph_diagonal * (90. - self.ph_viewangle).to_radians().tan()
-                )),
-                text(format!(
-                    "Vignetting {:.1} f-stops at {:.1} degrees view angle",
-                    //self.ph_diagonal * (90. - self.ph_viewangle).to_radians().tan()
-                    pinhole::stop_equivalent(self.calc_vignetting().0),
-                    2. * self.calc_vignetting().1
-                )),
-            ],
-            horizontal_rule(48),
-            // Optimal size calculation.
-            column![
-                text("Optimal size").size(24),
-                row![
-                    text(format!("Wavelength {:.0} nm  ", self.ph_wavelength))
-                        .width(Length::FillPortion(1)),
-                    slider(400.0..=700.0, self.ph_wavelength, |v| {
-                        Message::UpdatePhWavelength(v)
-                    })
-                    .step(1.)
-                    .width(Length::FillPortion(4)),
-                ]
-                .padding(8),
-                row![
-                    text(format!("Focal length {:.0} mm  ", self.ph_focallength))
-                        .width(Length::FillPortion(1)),
-                    slider(1.0..=500., self.ph_focallength, |v| {
-                        Message::UpdatePhFocallength(v)
-                    })
-                    .step(1.)
-                    .shift_step(0.1)
-                    .width(Length::FillPortion(4)),
-                ]
-                .padding(8),
-                row![
-                    text(format!("Rayleigh factor {:.2}  ", self.ph_rayleighfactor))
-                        .width(Length::FillPortion(1)),
-                    slider(0.10..=3.00, self.ph_rayleighfactor, |v| {
-                        Message::UpdatePhRayleighFactor(v)
-                    })
-                    .step(0.01)
-                    .width(Length::FillPortion(4)),
-                ]
-                .padding(8),
-                text(format!(
-                    "Optimal pinhole diameter {:.2} mm",
-                    self.calc_optimalsize()
-                )),
-                text(format!(
-                    "Coverage radius based on focal length and view angle {:.0} mm",
-                    self.ph_focallength / (90.0 - (self.ph_viewangle)).to_radians().tan()
-                )),
-                text(format!(
-                    "Effective f-stop based on focal length and pinhole diameter f/{:.0}",
-                    self.ph_focallength / self.ph_diameter
-                )),
-                text(format!(
-                    "Distance from f/32 is {:.1} f-stops",
-                    pinhole::delta_thirds(32f32, self.ph_focallength / self.ph_diameter)
-                )),
-            ],
+            row![
+                button("Open").on_press(Message::Open),
+                button("Save").on_press(Message::Save),
+                button("Save As").on_press(Message::SaveAs),
+                text(&self.status),
+            ]
+            .spacing(8),
+            row![
+                text_input("Name", &self.name).on_input(Message::UpdateName),
+                text_input("Film", &self.film).on_input(Message::UpdateFilm),
+            ]
+            .spacing(8),
             horizontal_rule(48),
         ]
         .spacing(20)
-        .padding(20)
-        .into()
+        .padding(20);
+        for section in phcalc_view::SECTIONS.iter() {
+            let mut pane = column![text(section.title).size(24)];
+            for &input in section.inputs {
+                pane = pane.push(self.input_row(input));
+            }
+            for output in section.outputs.iter().filter(|o| o.applies(&self.point)) {
+                pane = pane.push(text(output.line(&self.point).to_string()));
+            }
+            page = page.push(pane).push(horizontal_rule(48));
+        }
+        page.into()
     }
 }
 
@@ -208,9 +190,21 @@ fn main() -> iced::Result {
         .run_with(AppState::new)
 }
 
-fn _third_stops(fstop: f32) -> f32 {
-    let base = 6f32.sqrt();
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    let n = 3.0 * fstop.log2() / base.log2();
-    n
+    #[test]
+    fn subject_distance_slider() {
+        let mut state = AppState::default();
+        state.set_design(Design::default());
+        let def = Input::SubjectDistance.def();
+        state.update(Message::UpdateInput(
+            Input::SubjectDistance,
+            *def.slider_range().end(),
+        ));
+        assert_eq!(state.point.subject_distance, f32::INFINITY);
+        state.update(Message::UpdateInput(Input::SubjectDistance, 1.));
+        assert_eq!(state.point.subject_distance, 10.);
+    }
 }
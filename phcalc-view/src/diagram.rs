@@ -0,0 +1,439 @@
+// Drawings of the camera to scale, in mm with y up: a cross-section with the
+// pinhole at the origin and the optical axis along x, and the film seen from
+// the front. Drawn by the GUIs and written as SVG.
+use std::fmt::Write;
+
+use pinhole::sweep::Point;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Style {
+    Axis,
+    Plate,
+    Cone,
+    Film,
+    ImageCircle,
+    Format,
+    OtherFormat,
+    Coverage,
+    // Illumination this many ½ stops darker than the centre.
+    Shade(u8),
+    Label,
+}
+
+impl Style {
+    pub fn color(&self) -> [u8; 3] {
+        match self {
+            Style::Axis => [128, 128, 128],
+            Style::Plate => [160, 160, 170],
+            Style::Cone => [230, 150, 60],
+            Style::Film => [200, 200, 200],
+            Style::ImageCircle => [90, 160, 230],
+            Style::Format => [120, 200, 120],
+            Style::OtherFormat => [110, 130, 110],
+            Style::Coverage => [230, 150, 60],
+            Style::Shade(level) => {
+                let v = 40 + (120. * 2f32.powf(-0.5 * *level as f32)) as u8;
+                [v, v, v]
+            }
+            Style::Label => [180, 180, 180],
+        }
+    }
+
+    // Stroke width in pixels, the drawing is to scale but the lines are not.
+    pub fn width(&self) -> f32 {
+        match self {
+            Style::ImageCircle | Style::Format => 4.,
+            Style::Shade(_) => 0.,
+            Style::Axis | Style::Label | Style::OtherFormat => 1.,
+            _ => 1.5,
+        }
+    }
+
+    pub fn dashed(&self) -> bool {
+        matches!(self, Style::Axis | Style::OtherFormat)
+    }
+
+    pub fn filled(&self) -> bool {
+        matches!(self, Style::Plate | Style::Shade(_))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Anchor {
+    Start,
+    Middle,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Shape {
+    Line {
+        from: [f32; 2],
+        to: [f32; 2],
+        style: Style,
+    },
+    Rect {
+        min: [f32; 2],
+        max: [f32; 2],
+        style: Style,
+    },
+    Circle {
+        centre: [f32; 2],
+        radius: f32,
+        style: Style,
+    },
+    Text {
+        at: [f32; 2],
+        text: String,
+        anchor: Anchor,
+    },
+}
+
+// Half stops of falloff shaded on the front view, at most.
+const SHADES: u8 = 16;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagram {
+    pub min: [f32; 2],
+    pub max: [f32; 2],
+    pub shapes: Vec<Shape>,
+}
+
+impl Diagram {
+    // Side view: plate, light cone, film plane, image circle and format.
+    pub fn section(p: &Point) -> Self {
+        use Shape::{Line, Rect, Text};
+        let f = p.focal_length;
+        let image = p.projection / 2.;
+        let half_long = p.format.map_or(0., |format| format.radii()[1]);
+        // Room for the image circle and the format, the cone runs off the
+        // drawing when it covers much more.
+        let top = 1.3 * image.max(half_long).max(0.25 * f);
+        let (min, max) = ([-0.15 * f, -top], [1.35 * f, top]);
+        let angle = p.viewangle();
+        let coverage = p.coverage_radius();
+        let cone_end = if coverage <= top {
+            [f, coverage]
+        } else {
+            [top / angle.to_radians().tan(), top]
+        };
+        let mut shapes = vec![
+            Line {
+                from: [min[0], 0.],
+                to: [f, 0.],
+                style: Style::Axis,
+            },
+            Rect {
+                min: [0., p.diameter / 2.],
+                max: [p.thickness, top],
+                style: Style::Plate,
+            },
+            Rect {
+                min: [0., -top],
+                max: [p.thickness, -p.diameter / 2.],
+                style: Style::Plate,
+            },
+            Line {
+                from: [0., 0.],
+                to: cone_end,
+                style: Style::Cone,
+            },
+            Line {
+                from: [0., 0.],
+                to: [cone_end[0], -cone_end[1]],
+                style: Style::Cone,
+            },
+            Line {
+                from: [f, -top],
+                to: [f, top],
+                style: Style::Film,
+            },
+            Line {
+                from: [f, -image],
+                to: [f, image],
+                style: Style::ImageCircle,
+            },
+            Text {
+                at: [0.02 * f, 0.9 * top],
+                text: format!("plate {:.2} mm, Ø {:.2} mm", p.thickness, p.diameter),
+                anchor: Anchor::Start,
+            },
+            Text {
+                at: [0.3 * f, 0.04 * top],
+                text: format!("{:.1}˚", 2. * angle),
+                anchor: Anchor::Middle,
+            },
+            Text {
+                at: [f / 2., -0.9 * top],
+                text: format!("focal length {:.1} mm", f),
+                anchor: Anchor::Middle,
+            },
+            Text {
+                at: [1.03 * f, image],
+                text: format!("image Ø {:.0} mm", p.projection),
+                anchor: Anchor::Start,
+            },
+        ];
+        if let Some(format) = p.format {
+            // The long side of the format lies across the drawing.
+            let x = 1.015 * f;
+            shapes.push(Line {
+                from: [x, -half_long],
+                to: [x, half_long],
+                style: Style::Format,
+            });
+            shapes.push(Text {
+                at: [1.03 * f, -half_long],
+                text: format.name.to_string(),
+                anchor: Anchor::Start,
+            });
+        }
+        Diagram { min, max, shapes }
+    }
+
+    // Front view of the film: the illumination in ½ stop rings, the circle the
+    // plate lets light through to, the image circle and the formats in it.
+    pub fn front(p: &Point) -> Self {
+        use Shape::{Circle, Rect, Text};
+        let image = p.projection / 2.;
+        let reach = image.max(p.format.map_or(0., |f| f.radii()[2]));
+        // Catalogue formats of about this size for comparison.
+        let others: Vec<_> = pinhole::formats::FORMATS
+            .iter()
+            .filter(|f| Some(*f) != p.format && f.radii()[2] <= 1.5 * reach)
+            .collect();
+        let extent = 1.1 * others.iter().map(|f| f.radii()[2]).fold(reach, f32::max);
+        let (min, max) = ([-extent, -extent], [extent, extent]);
+        let mut shapes = vec![Rect {
+            min,
+            max,
+            style: Style::Shade(SHADES),
+        }];
+        // Darkest first, each ring lighter on top of the last.
+        for level in (1..=SHADES).rev() {
+            let radius = pinhole::falloff::radius_at_stops(p, 0.5 * level as f32);
+            if radius < extent * std::f32::consts::SQRT_2 {
+                shapes.push(Circle {
+                    centre: [0., 0.],
+                    radius,
+                    style: Style::Shade(level - 1),
+                });
+            }
+        }
+        let coverage = p.coverage_radius();
+        shapes.push(Circle {
+            centre: [0., 0.],
+            radius: coverage,
+            style: Style::Coverage,
+        });
+        shapes.push(Circle {
+            centre: [0., 0.],
+            radius: image,
+            style: Style::ImageCircle,
+        });
+        let formats = others
+            .iter()
+            .map(|f| (*f, Style::OtherFormat))
+            .chain(p.format.map(|f| (f, Style::Format)));
+        for (f, style) in formats {
+            let [short, long, _] = f.radii();
+            shapes.push(Rect {
+                min: [-long, -short],
+                max: [long, short],
+                style,
+            });
+            shapes.push(Text {
+                at: [-long + 0.02 * extent, short - 0.06 * extent],
+                text: f.name.to_string(),
+                anchor: Anchor::Start,
+            });
+        }
+        shapes.push(Text {
+            at: [0., image + 0.02 * extent],
+            text: format!("image Ø {:.0} mm", p.projection),
+            anchor: Anchor::Middle,
+        });
+        if coverage < extent {
+            shapes.push(Text {
+                at: [0., -coverage - 0.06 * extent],
+                text: format!("light Ø {:.0} mm", 2. * coverage),
+                anchor: Anchor::Middle,
+            });
+        }
+        Diagram { min, max, shapes }
+    }
+
+    pub fn width(&self) -> f32 {
+        self.max[0] - self.min[0]
+    }
+
+    pub fn height(&self) -> f32 {
+        self.max[1] - self.min[1]
+    }
+
+    // The drawing scaled to this many pixels wide, y pointing down.
+    pub fn to_svg(&self, width: f32) -> String {
+        let scale = width / self.width();
+        let height = self.height() * scale;
+        let at = |[x, y]: [f32; 2]| ((x - self.min[0]) * scale, (self.max[1] - y) * scale);
+        let mut svg = String::new();
+        let _ = writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width:.0}" height="{height:.0}" viewBox="0 0 {width:.1} {height:.1}">"#
+        );
+        let _ = writeln!(
+            svg,
+            r#"<rect width="100%" height="100%" fill="rgb(30,30,30)"/>"#
+        );
+        for shape in &self.shapes {
+            let _ = match shape {
+                Shape::Line { from, to, style } => {
+                    let ((x1, y1), (x2, y2)) = (at(*from), at(*to));
+                    writeln!(
+                        svg,
+                        r#"<line x1="{x1:.2}" y1="{y1:.2}" x2="{x2:.2}" y2="{y2:.2}" {}/>"#,
+                        stroke(*style)
+                    )
+                }
+                Shape::Rect { min, max, style } => {
+                    let ((x, y), (x2, y2)) = (at([min[0], max[1]]), at([max[0], min[1]]));
+                    writeln!(
+                        svg,
+                        r#"<rect x="{x:.2}" y="{y:.2}" width="{:.2}" height="{:.2}" {}/>"#,
+                        (x2 - x).max(0.5),
+                        y2 - y,
+                        paint(*style)
+                    )
+                }
+                Shape::Circle {
+                    centre,
+                    radius,
+                    style,
+                } => {
+                    let (cx, cy) = at(*centre);
+                    writeln!(
+                        svg,
+                        r#"<circle cx="{cx:.2}" cy="{cy:.2}" r="{:.2}" {}/>"#,
+                        radius * scale,
+                        paint(*style)
+                    )
+                }
+                Shape::Text {
+                    at: pos,
+                    text,
+                    anchor,
+                } => {
+                    let (x, y) = at(*pos);
+                    writeln!(
+                        svg,
+                        r#"<text x="{x:.2}" y="{y:.2}" fill="{}" font-family="sans-serif" font-size="12" text-anchor="{}">{}</text>"#,
+                        rgb(Style::Label.color()),
+                        match anchor {
+                            Anchor::Start => "start",
+                            Anchor::Middle => "middle",
+                        },
+                        escape(text)
+                    )
+                }
+            };
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+}
+
+fn rgb([r, g, b]: [u8; 3]) -> String {
+    format!("rgb({r},{g},{b})")
+}
+
+// Fill and stroke of a rect or circle.
+fn paint(style: Style) -> String {
+    let fill = if style.filled() {
+        rgb(style.color())
+    } else {
+        "none".to_string()
+    };
+    format!(r#"fill="{fill}" {}"#, stroke(style))
+}
+
+fn stroke(style: Style) -> String {
+    let dash = if style.dashed() {
+        r#" stroke-dasharray="6 4""#
+    } else {
+        ""
+    };
+    format!(
+        r#"stroke="{}" stroke-width="{}"{dash}"#,
+        rgb(style.color()),
+        style.width()
+    )
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cone_stays_in_the_drawing() {
+        // The default design covers 750 mm, far more than the drawing.
+        let d = Diagram::section(&crate::defaults());
+        for shape in &d.shapes {
+            if let Shape::Line { to, .. } = shape {
+                assert!(to[1] <= d.max[1] && to[1] >= d.min[1]);
+            }
+        }
+    }
+
+    #[test]
+    fn cone_reaches_coverage() {
+        let p = Point {
+            diameter: 0.3,
+            thickness: 1.,
+            ..crate::defaults()
+        };
+        let d = Diagram::section(&p);
+        assert!(d.shapes.contains(&Shape::Line {
+            from: [0., 0.],
+            to: [p.focal_length, p.coverage_radius()],
+            style: Style::Cone,
+        }));
+    }
+
+    #[test]
+    fn svg() {
+        let d = Diagram::section(&crate::defaults());
+        let svg = d.to_svg(600.);
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.ends_with("</svg>\n"));
+        assert_eq!(svg.matches("<text").count(), 5);
+        assert!(svg.contains(">35mm</text>"));
+    }
+
+    #[test]
+    fn front_view() {
+        let mut p = crate::defaults();
+        crate::select_format(&mut p, pinhole::formats::find("6x9").unwrap());
+        let d = Diagram::front(&p);
+        // Formats up to half as large again as the 6x9 image circle.
+        let names: Vec<_> = d
+            .shapes
+            .iter()
+            .filter_map(|s| match s {
+                Shape::Text { text, .. } if !text.contains('Ø') => Some(text.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            names,
+            ["35mm", "645", "6x6", "6x7", "6x12", "4\"x5\"", "6x9"]
+        );
+        let svg = d.to_svg(400.);
+        assert!(svg.matches("<circle").count() > 2);
+    }
+}
@@ -0,0 +1,63 @@
+// Opening and saving designs on a path the GUI's file dialog gave. Ok and Err
+// are both the line for the status bar.
+use std::path::Path;
+
+use pinhole::design::{Design, EXTENSION};
+
+// For the dialogs' file type filter.
+pub const FILTER_NAME: &str = "Pinhole design";
+pub const FILTER_EXTENSIONS: [&str; 2] = ["toml", "json"];
+
+pub fn open(path: &Path) -> Result<(Design, String), String> {
+    match Design::load(path) {
+        Ok(d) => Ok((d, format!("Opened {}", path.display()))),
+        Err(e) => Err(format!("Could not open {}: {e}", path.display())),
+    }
+}
+
+// The design's name with the extension, "camera" without a name.
+pub fn default_name(design: &Design) -> String {
+    let name = if design.name.is_empty() {
+        "camera"
+    } else {
+        &design.name
+    };
+    format!("{name}.{EXTENSION}")
+}
+
+pub fn save(design: &Design, path: &Path) -> Result<String, String> {
+    match design.save(path) {
+        Ok(()) => Ok(format!("Saved {}", path.display())),
+        Err(e) => Err(format!("Could not save {}: {e}", path.display())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names() {
+        let mut d = Design {
+            name: String::new(),
+            ..Default::default()
+        };
+        assert_eq!(default_name(&d), "camera.pinhole.toml");
+        d.name = "4x5 box".to_string();
+        assert_eq!(default_name(&d), "4x5 box.pinhole.toml");
+    }
+
+    #[test]
+    fn status_lines() {
+        let path = std::env::temp_dir().join("phcalc-view-file-test.pinhole.toml");
+        let d = Design {
+            name: "Zero".to_string(),
+            ..Default::default()
+        };
+        let shown = path.display();
+        assert_eq!(save(&d, &path), Ok(format!("Saved {shown}")));
+        assert_eq!(open(&path), Ok((d, format!("Opened {shown}"))));
+        std::fs::remove_file(&path).unwrap();
+        assert!(open(&path).is_err_and(|e| e.starts_with(&format!("Could not open {shown}: "))));
+    }
+}
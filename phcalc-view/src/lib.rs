@@ -0,0 +1,551 @@
+// What the calculators show, without a toolkit: the inputs with their ranges
+// and the result lines. phcalc-egui and phcalc-iced only draw this, and show
+// the file dialogs for file.
+pub mod diagram;
+pub mod file;
+pub mod timer;
+
+use std::fmt;
+use std::ops::RangeInclusive;
+
+use pinhole::closeup::CloseUp;
+use pinhole::stops::Division;
+use pinhole::sweep::Point;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Input {
+    Diameter,
+    Thickness,
+    FocalLength,
+    Projection,
+    Wavelength,
+    RayleighFactor,
+    SubjectDistance,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scale {
+    Linear,
+    Log,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InputDef {
+    pub label: &'static str,
+    pub unit: &'static str,
+    pub min: f32,
+    pub max: f32,
+    pub default: f32,
+    pub scale: Scale,
+    pub decimals: usize,
+    // Past max comes infinity, for the subject distance.
+    pub infinite: bool,
+}
+
+const DIAMETER: InputDef = InputDef {
+    label: "Pinhole Ø",
+    unit: "mm",
+    min: 0.01,
+    max: 2.,
+    default: 0.3,
+    scale: Scale::Linear,
+    decimals: 3,
+    infinite: false,
+};
+const THICKNESS: InputDef = InputDef {
+    label: "Pinhole thickness",
+    unit: "mm",
+    min: 0.01,
+    max: 1.,
+    default: 0.04,
+    scale: Scale::Linear,
+    decimals: 3,
+    infinite: false,
+};
+const FOCALLENGTH: InputDef = InputDef {
+    label: "Focal length",
+    unit: "mm",
+    min: 1.,
+    max: 10000.,
+    default: 50.,
+    scale: Scale::Log,
+    decimals: 1,
+    infinite: false,
+};
+const PROJECTION: InputDef = InputDef {
+    label: "Desired projection Ø",
+    unit: "mm",
+    min: 10.,
+    max: 1000.,
+    default: 44.,
+    scale: Scale::Linear,
+    decimals: 0,
+    infinite: false,
+};
+const WAVELENGTH: InputDef = InputDef {
+    label: "Wavelength",
+    unit: "nm",
+    min: 350.,
+    max: 700.,
+    default: 550.,
+    scale: Scale::Linear,
+    decimals: 0,
+    infinite: false,
+};
+const RAYLEIGHFACTOR: InputDef = InputDef {
+    label: "Rayleigh factor",
+    unit: "",
+    min: 1.,
+    max: 2.,
+    default: 1.56,
+    scale: Scale::Linear,
+    decimals: 2,
+    infinite: false,
+};
+const SUBJECTDIST: InputDef = InputDef {
+    label: "Subject distance",
+    unit: "m",
+    min: 0.01,
+    max: 1000.,
+    default: f32::INFINITY,
+    scale: Scale::Log,
+    decimals: 2,
+    infinite: true,
+};
+
+impl Input {
+    pub fn def(&self) -> &'static InputDef {
+        match self {
+            Input::Diameter => &DIAMETER,
+            Input::Thickness => &THICKNESS,
+            Input::FocalLength => &FOCALLENGTH,
+            Input::Projection => &PROJECTION,
+            Input::Wavelength => &WAVELENGTH,
+            Input::RayleighFactor => &RAYLEIGHFACTOR,
+            Input::SubjectDistance => &SUBJECTDIST,
+        }
+    }
+
+    pub fn value(&self, p: &Point) -> f32 {
+        let mut p = *p;
+        *self.value_mut(&mut p)
+    }
+
+    pub fn value_mut<'a>(&self, p: &'a mut Point) -> &'a mut f32 {
+        match self {
+            Input::Diameter => &mut p.diameter,
+            Input::Thickness => &mut p.thickness,
+            Input::FocalLength => &mut p.focal_length,
+            Input::Projection => &mut p.projection,
+            Input::Wavelength => &mut p.wavelength,
+            Input::RayleighFactor => &mut p.rayleigh_factor,
+            Input::SubjectDistance => &mut p.subject_distance,
+        }
+    }
+
+    // Sets the value and keeps the format in step with the projection.
+    pub fn set(&self, p: &mut Point, value: f32) {
+        *self.value_mut(p) = value;
+        sync_format(p);
+    }
+}
+
+impl InputDef {
+    // "Pinhole Ø (mm)"
+    pub fn label_with_unit(&self) -> String {
+        match self.unit {
+            "" => self.label.to_string(),
+            unit => format!("{} ({unit})", self.label),
+        }
+    }
+
+    pub fn format_value(&self, v: f32) -> String {
+        if v.is_infinite() {
+            return "∞".to_string();
+        }
+        Quantity::new(v, self.decimals, self.unit).to_string()
+    }
+
+    // For toolkits with linear sliders only: where a value sits on the slider,
+    // and back. Log inputs run over log10 of the value, infinity is the far end.
+    pub fn slider_range(&self) -> RangeInclusive<f32> {
+        match self.scale {
+            Scale::Linear => self.min..=self.max,
+            Scale::Log => self.min.log10()..=self.max.log10(),
+        }
+    }
+
+    pub fn to_slider(&self, v: f32) -> f32 {
+        let range = self.slider_range();
+        let s = match self.scale {
+            Scale::Linear => v,
+            Scale::Log => v.log10(),
+        };
+        s.clamp(*range.start(), *range.end())
+    }
+
+    pub fn from_slider(&self, s: f32) -> f32 {
+        if self.infinite && s >= *self.slider_range().end() {
+            return f32::INFINITY;
+        }
+        match self.scale {
+            Scale::Linear => s,
+            Scale::Log => 10f32.powf(s),
+        }
+    }
+
+    // A step of one shown decimal, or a hundredth of a decade on log sliders.
+    pub fn slider_step(&self) -> f32 {
+        match self.scale {
+            Scale::Linear => 10f32.powi(-(self.decimals as i32)),
+            Scale::Log => 0.01,
+        }
+    }
+}
+
+// The projection decides which catalogue format is shown as selected.
+pub fn sync_format(p: &mut Point) {
+    p.format = pinhole::formats::from_diameter(p.projection);
+}
+
+pub fn select_format(p: &mut Point, format: &'static pinhole::formats::Format) {
+    p.projection = format.projection_diameter();
+    p.format = Some(format);
+}
+
+// Name of the selected format, or the side of a square with the same diagonal.
+pub fn format_label(p: &Point) -> String {
+    match p.format {
+        Some(f) => f.name.to_string(),
+        None => format!("{:.1} sq", ((p.projection * p.projection) / 2.0).sqrt()),
+    }
+}
+
+// A number with its precision and unit.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Quantity {
+    pub value: f32,
+    pub decimals: usize,
+    pub unit: &'static str,
+}
+
+impl Quantity {
+    pub fn new(value: f32, decimals: usize, unit: &'static str) -> Self {
+        Self {
+            value,
+            decimals,
+            unit,
+        }
+    }
+}
+
+impl fmt::Display for Quantity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:.*}", self.decimals, self.value)?;
+        match self.unit {
+            "" => Ok(()),
+            "˚" => write!(f, "˚"),
+            unit => write!(f, " {unit}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Part {
+    Text(&'static str),
+    Quantity(Quantity),
+    // Worked out text, like a stop offset.
+    Label(String),
+}
+
+// One result sentence, text and numbers apart so a GUI can style them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Line(pub Vec<Part>);
+
+impl fmt::Display for Line {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for part in &self.0 {
+            match part {
+                Part::Text(s) => write!(f, "{s}")?,
+                Part::Quantity(q) => write!(f, "{q}")?,
+                Part::Label(s) => write!(f, "{s}")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Output {
+    ViewAngle,
+    NeededFocalLength,
+    FStop,
+    Vignetting,
+    OptimalSize,
+    // Only with the subject at a finite distance.
+    CloseUp,
+    SubjectField,
+}
+
+impl Output {
+    pub fn applies(&self, p: &Point) -> bool {
+        match self {
+            Output::CloseUp | Output::SubjectField => p.subject_distance.is_finite(),
+            _ => true,
+        }
+    }
+
+    pub fn line(&self, p: &Point) -> Line {
+        use Part::{Label as L, Quantity as Q, Text as T};
+        let q = Quantity::new;
+        Line(match self {
+            Output::ViewAngle => vec![
+                T("View angle is "),
+                Q(q(2. * p.viewangle(), 1, "˚")),
+                T(" which covers a diameter of "),
+                Q(q(2. * p.coverage_radius(), 1, "mm")),
+            ],
+            Output::NeededFocalLength => vec![
+                T("Focal length needed to cover the projection Ø is "),
+                Q(q(p.needed_focallength(), 0, "mm")),
+            ],
+            Output::FStop => {
+                let n = p.fnumber();
+                let stops = pinhole::stops::between_fnumbers(32., n);
+                vec![
+                    T("F-stop is f/"),
+                    Q(q(n, 1, "")),
+                    T(" ("),
+                    L(pinhole::stops::offset(n, Division::Third).to_string()),
+                    T("), "),
+                    L(pinhole::stops::thirds(stops)),
+                    T(" stops from f/32: exposure × "),
+                    Q(q(pinhole::stops::time(1., stops), 1, "")),
+                ]
+            }
+            Output::Vignetting => {
+                let (falloff, angle) = p.vignetting();
+                vec![
+                    T("Vignetting for desired projection Ø is "),
+                    Q(q(pinhole::stop_equivalent(falloff), 1, "")),
+                    T(" f-stops ("),
+                    Q(q(falloff, 2, "")),
+                    T(") at a "),
+                    Q(q(angle, 1, "˚")),
+                    T(" angle"),
+                ]
+            }
+            Output::OptimalSize => vec![
+                T("Optimal pinhole Ø for this focal length is "),
+                Q(q(p.optimalsize(), 2, "mm")),
+                T(" (at "),
+                Q(q(p.magnification(), 1, "")),
+                T(" magnification)"),
+            ],
+            Output::CloseUp | Output::SubjectField => {
+                let Some(c) = CloseUp::new(p) else {
+                    return Line(vec![T("Subject at infinity")]);
+                };
+                if *self == Output::CloseUp {
+                    vec![
+                        T("Close up the exposure is × "),
+                        Q(q(c.exposure_factor, 2, "")),
+                        T(" ("),
+                        L(pinhole::stops::thirds(c.stops)),
+                        T(" stops) against f/"),
+                        Q(q(c.effective_fnumber(p.diameter), 1, "")),
+                        T(" at the effective focal length of "),
+                        Q(q(c.effective_focal_length, 1, "mm")),
+                        T(", the f-stop above includes it"),
+                    ]
+                } else {
+                    vec![
+                        T("Subject field is "),
+                        Q(q(c.field.0, 0, "mm")),
+                        T(" × "),
+                        Q(q(c.field.1, 0, "mm")),
+                        T(" on "),
+                        L(format_label(p)),
+                        T(", optimal pinhole Ø "),
+                        Q(q(c.optimal_diameter, 2, "mm")),
+                    ]
+                }
+            }
+        })
+    }
+}
+
+pub struct Section {
+    pub title: &'static str,
+    pub inputs: &'static [Input],
+    pub outputs: &'static [Output],
+}
+
+pub const SECTIONS: [Section; 2] = [
+    Section {
+        title: "View angle",
+        inputs: &[
+            Input::Diameter,
+            Input::Thickness,
+            Input::FocalLength,
+            Input::Projection,
+        ],
+        outputs: &[
+            Output::ViewAngle,
+            Output::FStop,
+            Output::NeededFocalLength,
+            Output::Vignetting,
+        ],
+    },
+    Section {
+        title: "Optimal size",
+        inputs: &[
+            Input::Wavelength,
+            Input::RayleighFactor,
+            Input::SubjectDistance,
+        ],
+        outputs: &[Output::OptimalSize, Output::CloseUp, Output::SubjectField],
+    },
+];
+
+// A point with every input at its default.
+pub fn defaults() -> Point {
+    let mut p = Point::default();
+    for section in SECTIONS.iter() {
+        for input in section.inputs {
+            input.set(&mut p, input.def().default);
+        }
+    }
+    p
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn defaults_match_library() {
+        let mut expected = Point::default();
+        sync_format(&mut expected);
+        assert_eq!(defaults(), expected);
+    }
+
+    #[test]
+    fn lines() {
+        let p = defaults();
+        assert_eq!(
+            Output::ViewAngle.line(&p).to_string(),
+            "View angle is 164.8˚ which covers a diameter of 750.0 mm"
+        );
+        assert_eq!(
+            Output::OptimalSize.line(&p).to_string(),
+            "Optimal pinhole Ø for this focal length is 0.26 mm (at 0.0 magnification)"
+        );
+        assert_eq!(
+            Output::FStop.line(&p).to_string(),
+            "F-stop is f/166.7 (f/128 + ⅔), 4⅔ stops from f/32: exposure × 27.1"
+        );
+    }
+
+    // What both GUIs show for a design is what the library works out for it.
+    #[test]
+    fn lines_match_library() {
+        let box4x5 = pinhole::design::Design {
+            name: "4x5 box".to_string(),
+            focal_length: 90.,
+            diameter: 0.4,
+            thickness: 0.05,
+            projection: 163.,
+            subject_distance: 1.5,
+            ..Default::default()
+        };
+        for d in [pinhole::design::Design::default(), box4x5] {
+            let p = d.point();
+            let (f, radius) = (d.focal_length, d.projection / 2.);
+            let half = pinhole::calc_viewangle(d.diameter, d.thickness);
+            let n = pinhole::fnumber(f, d.diameter);
+            let (falloff, angle) = pinhole::calc_vignetting(f, radius);
+            let m = f / (d.subject_distance * 1000.);
+            let optimal = pinhole::calc_optimalsize(f, d.wavelength, d.rayleigh_factor, m);
+            for section in SECTIONS.iter() {
+                for output in section.outputs.iter().filter(|o| o.applies(&p)) {
+                    let expected = match output {
+                        Output::ViewAngle => {
+                            vec![2. * half, 2. * pinhole::coverage_radius(f, half)]
+                        }
+                        Output::NeededFocalLength => {
+                            vec![pinhole::needed_focallength(radius, half)]
+                        }
+                        Output::FStop => vec![n, pinhole::exposure_factor(32., n)],
+                        Output::Vignetting => {
+                            vec![pinhole::stop_equivalent(falloff), falloff, angle]
+                        }
+                        Output::OptimalSize => vec![optimal, m],
+                        Output::CloseUp => vec![
+                            (1. + m).powi(2),
+                            pinhole::fnumber(f / (1. + m), d.diameter),
+                            f / (1. + m),
+                        ],
+                        Output::SubjectField => {
+                            let format = p.format.expect("a catalogue format");
+                            vec![format.width / m, format.height / m, optimal]
+                        }
+                    };
+                    let shown: Vec<f32> = output
+                        .line(&p)
+                        .0
+                        .iter()
+                        .filter_map(|part| match part {
+                            Part::Quantity(q) => Some(q.value),
+                            _ => None,
+                        })
+                        .collect();
+                    assert_eq!(shown.len(), expected.len(), "{output:?} of {}", d.name);
+                    for (a, b) in shown.iter().zip(&expected) {
+                        assert!(
+                            (a - b).abs() <= 1e-4 * b.abs(),
+                            "{output:?} of {}: {a} {b}",
+                            d.name
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn close_up_lines() {
+        let mut p = defaults();
+        assert!(!Output::CloseUp.applies(&p));
+        assert!(Output::OptimalSize.applies(&p));
+        p.subject_distance = 0.2;
+        select_format(&mut p, pinhole::formats::find("6x9").unwrap());
+        assert!(Output::SubjectField.applies(&p));
+        assert_eq!(
+            Output::CloseUp.line(&p).to_string(),
+            "Close up the exposure is × 1.56 (⅔ stops) against f/133.3 at the effective focal \
+             length of 40.0 mm, the f-stop above includes it"
+        );
+        assert_eq!(
+            Output::SubjectField.line(&p).to_string(),
+            "Subject field is 240 mm × 360 mm on 6x9, optimal pinhole Ø 0.23 mm"
+        );
+    }
+
+    #[test]
+    fn log_slider_with_infinity() {
+        let def = Input::SubjectDistance.def();
+        assert_eq!(def.from_slider(*def.slider_range().end()), f32::INFINITY);
+        assert_eq!(def.to_slider(f32::INFINITY), *def.slider_range().end());
+        assert_eq!(def.from_slider(def.to_slider(10.)), 10.);
+        assert_eq!(def.format_value(f32::INFINITY), "∞");
+    }
+
+    #[test]
+    fn select_format_sets_projection() {
+        let mut p = defaults();
+        select_format(&mut p, pinhole::formats::find("6x9").unwrap());
+        assert_eq!(p.projection, 109.);
+        assert_eq!(format_label(&p), "6x9");
+    }
+}
@@ -0,0 +1,127 @@
+// Counting an exposure down. Times are seconds on any steady clock, the GUIs
+// pass their frame time in.
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum State {
+    Idle,
+    Running { end: f64 },
+    Paused { remaining: f64, since: f64 },
+    Done,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Timer {
+    pub total: f64,
+    // Light lost while paused, a passing cloud. Infinite for a closed shutter.
+    pub pause_stops: f32,
+    pub state: State,
+    started: f64,
+}
+
+impl Timer {
+    pub fn new(total: f64) -> Timer {
+        Timer {
+            total,
+            pause_stops: 2.,
+            state: State::Idle,
+            started: 0.,
+        }
+    }
+
+    pub fn start(&mut self, now: f64) {
+        self.started = now;
+        self.state = State::Running {
+            end: now + self.total,
+        };
+    }
+
+    // Exposure still to go. A pause only counts for the light that got
+    // through, so the rest of the exposure gets longer with it.
+    pub fn remaining(&self, now: f64) -> f64 {
+        match self.state {
+            State::Idle => self.total,
+            State::Running { end } => (end - now).max(0.),
+            State::Paused { remaining, since } => {
+                let light = 2f64.powf(-self.pause_stops as f64);
+                (remaining - light * (now - since)).max(0.)
+            }
+            State::Done => 0.,
+        }
+    }
+
+    pub fn pause(&mut self, now: f64) {
+        if let State::Running { .. } = self.state {
+            self.state = State::Paused {
+                remaining: self.remaining(now),
+                since: now,
+            };
+        }
+    }
+
+    pub fn resume(&mut self, now: f64) {
+        if let State::Paused { .. } = self.state {
+            self.state = State::Running {
+                end: now + self.remaining(now),
+            };
+        }
+    }
+
+    pub fn stop(&mut self) {
+        self.state = State::Idle;
+    }
+
+    // Call every frame, returns the seconds the exposure took when it has
+    // just finished.
+    pub fn tick(&mut self, now: f64) -> Option<f64> {
+        match self.state {
+            State::Running { end } if now >= end => {
+                self.state = State::Done;
+                Some(end - self.started)
+            }
+            _ => None,
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        matches!(self.state, State::Running { .. } | State::Paused { .. })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_down_and_finishes() {
+        let mut t = Timer::new(90.);
+        t.start(10.);
+        assert_eq!(t.remaining(40.), 60.);
+        assert_eq!(t.tick(99.), None);
+        assert_eq!(t.tick(100.), Some(90.));
+        assert_eq!(t.state, State::Done);
+        assert_eq!(t.tick(101.), None);
+    }
+
+    #[test]
+    fn cloud_extends_the_rest() {
+        let mut t = Timer::new(100.);
+        t.start(0.);
+        t.pause(20.);
+        // Two stops less light: 40 s under the cloud count for 10.
+        assert_eq!(t.remaining(60.), 70.);
+        t.resume(60.);
+        assert_eq!(t.tick(129.), None);
+        assert_eq!(t.tick(130.), Some(130.));
+    }
+
+    #[test]
+    fn closed_shutter_stops_the_clock() {
+        let mut t = Timer::new(100.);
+        t.pause_stops = f32::INFINITY;
+        t.start(0.);
+        t.pause(30.);
+        assert_eq!(t.remaining(500.), 70.);
+        t.resume(500.);
+        assert_eq!(t.remaining(510.), 60.);
+    }
+}
@@ -0,0 +1,91 @@
+// Close up the subject distance matters. A pinhole with the film v behind it
+// images a subject u in front at magnification m = v/u, and works like a lens
+// of focal length uv/(u+v) = v/(1+m), the length calc_optimalsize sizes the
+// pinhole for. Against that lens's f-number the film gets (1+m)² less light,
+// the bellows factor; the f-number v/d already has it. Lengths in mm.
+use crate::sweep::Point;
+
+pub fn magnification(subject_distance: f32, image_distance: f32) -> f32 {
+    image_distance / subject_distance
+}
+
+// Exposure × this at magnification m.
+pub fn exposure_factor(m: f32) -> f32 {
+    (1. + m).powi(2)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CloseUp {
+    pub magnification: f32,
+    pub effective_focal_length: f32,
+    pub exposure_factor: f32,
+    pub stops: f32,
+    // What the format takes in at the subject, width and height. Without a
+    // format the square with the projection as diagonal.
+    pub field: (f32, f32),
+    pub optimal_diameter: f32,
+}
+
+impl CloseUp {
+    // None with the subject at infinity.
+    pub fn new(p: &Point) -> Option<CloseUp> {
+        if !p.subject_distance.is_finite() {
+            return None;
+        }
+        let m = p.magnification();
+        let factor = exposure_factor(m);
+        let (width, height) = match p.format {
+            Some(f) => (f.width, f.height),
+            None => (p.projection / 2f32.sqrt(), p.projection / 2f32.sqrt()),
+        };
+        Some(CloseUp {
+            magnification: m,
+            effective_focal_length: p.focal_length / (1. + m),
+            exposure_factor: factor,
+            stops: factor.log2(),
+            field: (width / m, height / m),
+            optimal_diameter: p.optimalsize(),
+        })
+    }
+
+    // The f-number of the lens the pinhole works like, the exposure factor
+    // less than the pinhole's own.
+    pub fn effective_fnumber(&self, diameter: f32) -> f32 {
+        crate::fnumber(self.effective_focal_length, diameter)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn life_size() {
+        let mut p = Point {
+            focal_length: 100.,
+            subject_distance: 0.1,
+            format: crate::formats::find("6x9"),
+            ..Default::default()
+        };
+        let c = CloseUp::new(&p).unwrap();
+        assert_eq!(c.magnification, 1.);
+        assert_eq!(c.effective_focal_length, 50.);
+        assert_eq!(c.exposure_factor, 4.);
+        assert_eq!(c.stops, 2.);
+        assert_eq!(c.field, (60., 90.));
+        // Sized as a 50 mm pinhole at infinity.
+        let far = crate::calc_optimalsize(50., p.wavelength, p.rayleigh_factor, 0.);
+        assert!((c.optimal_diameter - far).abs() < 1e-5);
+        assert!((c.effective_fnumber(0.5) * 2. - p.focal_length / 0.5).abs() < 1e-4);
+        // A metre away at a quarter of the size.
+        p.focal_length = 250.;
+        p.subject_distance = 1.;
+        let c = CloseUp::new(&p).unwrap();
+        assert_eq!(c.magnification, 0.25);
+        assert!((c.exposure_factor - 1.5625).abs() < 1e-6);
+        assert_eq!(c.field, (240., 360.));
+        p.subject_distance = f32::INFINITY;
+        assert_eq!(CloseUp::new(&p), None);
+        assert_eq!(p.magnification(), 0.);
+    }
+}
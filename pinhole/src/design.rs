@@ -0,0 +1,303 @@
+// Camera designs, saved as .pinhole.toml (or .json) files.
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use serde::de::{self, Visitor};
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::filters::{self, Stack};
+use crate::formats;
+use crate::sweep::Point;
+
+// Written into every file. Bump it when the layout changes and add a step to
+// `migrate` that upgrades the previous version.
+pub const VERSION: i64 = 1;
+
+pub const EXTENSION: &str = "pinhole.toml";
+
+// Lengths in mm, wavelength in nm and subject distance in m, as in the GUI.
+// The projection is a diameter; format is the name of the catalogue format it
+// was picked from, if any, and only used when the projection is left out.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Design {
+    pub version: i64,
+    pub name: String,
+    pub notes: String,
+    pub diameter: f32,
+    pub thickness: f32,
+    pub focal_length: f32,
+    pub format: Option<String>,
+    pub projection: f32,
+    pub wavelength: f32,
+    pub rayleigh_factor: f32,
+    pub film: String,
+    // Names from the filter database, in front of the pinhole.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub filters: Vec<String>,
+    #[serde(
+        skip_serializing_if = "is_infinite",
+        deserialize_with = "distance_or_infinity"
+    )]
+    pub subject_distance: f32,
+}
+
+impl Default for Design {
+    fn default() -> Self {
+        let p = Point::default();
+        Self {
+            version: VERSION,
+            name: String::new(),
+            notes: String::new(),
+            diameter: p.diameter,
+            thickness: p.thickness,
+            focal_length: p.focal_length,
+            format: formats::from_diameter(p.projection).map(|f| f.name.to_string()),
+            projection: p.projection,
+            wavelength: p.wavelength,
+            rayleigh_factor: p.rayleigh_factor,
+            film: String::new(),
+            filters: Vec::new(),
+            subject_distance: p.subject_distance,
+        }
+    }
+}
+
+fn is_infinite(x: &f32) -> bool {
+    x.is_infinite()
+}
+
+// A subject at infinity is left out of the file, JSON cannot hold it. A plain
+// number or null is accepted, as is an option in formats that spell those out.
+fn distance_or_infinity<'de, D: Deserializer<'de>>(d: D) -> Result<f32, D::Error> {
+    struct Distance;
+
+    impl<'de> Visitor<'de> for Distance {
+        type Value = f32;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "a distance in m, or nothing for infinity")
+        }
+
+        fn visit_f64<E: de::Error>(self, v: f64) -> Result<f32, E> {
+            Ok(v as f32)
+        }
+
+        fn visit_i64<E: de::Error>(self, v: i64) -> Result<f32, E> {
+            Ok(v as f32)
+        }
+
+        fn visit_u64<E: de::Error>(self, v: u64) -> Result<f32, E> {
+            Ok(v as f32)
+        }
+
+        fn visit_unit<E: de::Error>(self) -> Result<f32, E> {
+            Ok(f32::INFINITY)
+        }
+
+        fn visit_none<E: de::Error>(self) -> Result<f32, E> {
+            Ok(f32::INFINITY)
+        }
+
+        fn visit_some<D: Deserializer<'de>>(self, d: D) -> Result<f32, D::Error> {
+            d.deserialize_any(self)
+        }
+    }
+
+    d.deserialize_any(Distance)
+}
+
+#[derive(Debug)]
+pub enum DesignError {
+    Io(std::io::Error),
+    Parse(String),
+    // Written by a newer version of the program.
+    Version(i64),
+}
+
+impl fmt::Display for DesignError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DesignError::Io(e) => write!(f, "{e}"),
+            DesignError::Parse(e) => write!(f, "not a design file: {e}"),
+            DesignError::Version(v) => write!(
+                f,
+                "design file version {v} is newer than this program understands ({VERSION})"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for DesignError {}
+
+impl From<std::io::Error> for DesignError {
+    fn from(e: std::io::Error) -> Self {
+        DesignError::Io(e)
+    }
+}
+
+impl Design {
+    // The inputs of the calculations, see sweep::Point.
+    pub fn point(&self) -> Point {
+        Point {
+            diameter: self.diameter,
+            thickness: self.thickness,
+            focal_length: self.focal_length,
+            projection: self.projection,
+            format: formats::from_diameter(self.projection),
+            wavelength: self.wavelength,
+            rayleigh_factor: self.rayleigh_factor,
+            subject_distance: self.subject_distance,
+        }
+    }
+
+    pub fn stack(&self) -> Result<Stack, String> {
+        filters::stack(&self.filters)
+    }
+
+    // Take over the inputs of a calculation, keeping name, notes, film and
+    // filters.
+    pub fn set_point(&mut self, p: &Point) {
+        self.diameter = p.diameter;
+        self.thickness = p.thickness;
+        self.focal_length = p.focal_length;
+        self.format = p.format.map(|f| f.name.to_string());
+        self.projection = p.projection;
+        self.wavelength = p.wavelength;
+        self.rayleigh_factor = p.rayleigh_factor;
+        self.subject_distance = p.subject_distance;
+    }
+
+    pub fn to_toml(&self) -> Result<String, DesignError> {
+        toml::to_string(self).map_err(|e| DesignError::Parse(e.to_string()))
+    }
+
+    pub fn to_json(&self) -> Result<String, DesignError> {
+        serde_json::to_string_pretty(self).map_err(|e| DesignError::Parse(e.to_string()))
+    }
+
+    pub fn from_toml(s: &str) -> Result<Design, DesignError> {
+        let table: toml::Table =
+            toml::from_str(s).map_err(|e| DesignError::Parse(e.to_string()))?;
+        let value = serde_json::to_value(table).map_err(|e| DesignError::Parse(e.to_string()))?;
+        Design::from_value(value)
+    }
+
+    pub fn from_json(s: &str) -> Result<Design, DesignError> {
+        let value = serde_json::from_str(s).map_err(|e| DesignError::Parse(e.to_string()))?;
+        Design::from_value(value)
+    }
+
+    pub(crate) fn from_value(value: serde_json::Value) -> Result<Design, DesignError> {
+        let value = migrate(value)?;
+        let has_projection = value.get("projection").is_some();
+        let mut design: Design =
+            serde_json::from_value(value).map_err(|e| DesignError::Parse(e.to_string()))?;
+        // A hand-written file may only name the format.
+        if !has_projection && let Some(f) = design.format.as_deref().and_then(formats::find) {
+            design.projection = f.projection_diameter();
+        }
+        Ok(design)
+    }
+
+    // JSON for .json files, TOML for anything else.
+    pub fn load(path: &Path) -> Result<Design, DesignError> {
+        let s = fs::read_to_string(path)?;
+        if is_json(path) {
+            Design::from_json(&s)
+        } else {
+            Design::from_toml(&s)
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), DesignError> {
+        let s = if is_json(path) {
+            self.to_json()?
+        } else {
+            self.to_toml()?
+        };
+        write_replacing(path, &s)?;
+        Ok(())
+    }
+}
+
+// Written next to `path` and renamed over it, so a crash or a full disk
+// leaves the old file as it was.
+pub(crate) fn write_replacing(path: &Path, contents: &str) -> std::io::Result<()> {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let tmp = path.with_file_name(format!(".{name}.tmp"));
+    fs::write(&tmp, contents)
+        .and_then(|()| fs::rename(&tmp, path))
+        .inspect_err(|_| {
+            let _ = fs::remove_file(&tmp);
+        })
+}
+
+fn is_json(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|e| e.eq_ignore_ascii_case("json"))
+}
+
+// Upgrade the raw contents of an older file to the current layout. Files
+// without a version were written by hand and are read as version 1.
+fn migrate(mut value: serde_json::Value) -> Result<serde_json::Value, DesignError> {
+    let Some(table) = value.as_object_mut() else {
+        return Err(DesignError::Parse("expected a table".to_string()));
+    };
+    let version = table.get("version").and_then(|v| v.as_i64()).unwrap_or(1);
+    if version > VERSION {
+        return Err(DesignError::Version(version));
+    }
+    table.insert("version".to_string(), VERSION.into());
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn toml_roundtrip() {
+        let d = Design {
+            name: "Zero 2000".to_string(),
+            film: "HP5+".to_string(),
+            filters: vec!["Red 25".to_string(), "ND8".to_string()],
+            ..Default::default()
+        };
+        let back = Design::from_toml(&d.to_toml().unwrap()).unwrap();
+        assert_eq!(back, d);
+    }
+
+    #[test]
+    fn json_roundtrip_finite_distance() {
+        let d = Design {
+            subject_distance: 2.5,
+            ..Default::default()
+        };
+        let back = Design::from_json(&d.to_json().unwrap()).unwrap();
+        assert_eq!(back, d);
+    }
+
+    #[test]
+    fn hand_written_without_version() {
+        let d = Design::from_toml("name = \"4x5 box\"\nfocal_length = 90.0\n").unwrap();
+        assert_eq!(d.version, VERSION);
+        assert_eq!(d.focal_length, 90.);
+        assert_eq!(d.subject_distance, f32::INFINITY);
+        assert!(d.stack().unwrap().is_empty());
+    }
+
+    #[test]
+    fn format_without_projection() {
+        let d = Design::from_toml("format = \"6x9\"\n").unwrap();
+        assert_eq!(d.projection, 109.);
+        assert_eq!(d.point().format.unwrap().name, "6x9");
+    }
+
+    #[test]
+    fn newer_version_is_refused() {
+        let r = Design::from_toml("version = 99\n");
+        assert!(matches!(r, Err(DesignError::Version(99))));
+    }
+}
@@ -0,0 +1,203 @@
+// From a light reading to the time the pinhole needs. EV are light values at
+// ISO 100, EV 0 is 1 s at f/1.
+use serde::{Deserialize, Serialize};
+
+use crate::film::Film;
+use crate::stops;
+
+// Metered time in s at an f-number for a film speed.
+pub fn metered_time(ev: f32, iso: f32, n: f32) -> f32 {
+    stops::time(n * n, -ev - stops::between_isos(100., iso))
+}
+
+// A reading's EV at ISO 100, back from a time at an f-number and film speed.
+pub fn ev(time: f32, iso: f32, n: f32) -> f32 {
+    stops::between_times(time, n * n) - stops::between_isos(100., iso)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Exposure {
+    pub metered: f32,   // s at the pinhole's f-number
+    pub corrected: f32, // s with the film's reciprocity
+}
+
+impl Exposure {
+    // Stops of extra exposure for reciprocity.
+    pub fn extra_stops(&self) -> f32 {
+        stops::between_times(self.metered, self.corrected)
+    }
+}
+
+// A metered time in s, with the film's reciprocity when the film is known.
+pub fn for_metered(metered: f32, film: Option<&Film>) -> Exposure {
+    Exposure {
+        metered,
+        corrected: film.map_or(metered, |f| f.corrected(metered)),
+    }
+}
+
+// A meter reading of `time` at f-number `at`, through a pinhole of f-number `n`.
+pub fn at_pinhole(time: f32, at: f32, n: f32, film: Option<&Film>) -> Exposure {
+    for_metered(stops::time_at(time, at, n), film)
+}
+
+// The pinhole exposure for an EV on a film.
+pub fn for_ev(ev: f32, n: f32, film: &Film) -> Exposure {
+    for_metered(metered_time(ev, film.iso, n), Some(film))
+}
+
+// A light curve from readings, seconds from the start and EV. Straight lines
+// in between, the first and last reading before and after.
+pub fn from_points(points: &[(f32, f32)]) -> impl Fn(f32) -> Option<f32> + '_ {
+    move |t| match points {
+        [] => None,
+        [(t0, ev), ..] if t <= *t0 => Some(*ev),
+        [.., (t1, ev)] if t >= *t1 => Some(*ev),
+        _ => {
+            let i = points.iter().position(|(ti, _)| *ti >= t)?;
+            let ((t0, ev0), (t1, ev1)) = (points[i - 1], points[i]);
+            Some(ev0 + (ev1 - ev0) * (t - t0) / (t1 - t0))
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Fading {
+    // What the light asks for when there first is some, reciprocity
+    // included. A run started before dawn counts from then.
+    pub at_start: Exposure,
+    // How long it takes as the light changes, s.
+    pub duration: f32,
+}
+
+// The exposure under changing light. Each moment does its share of the
+// exposure the light of that moment needs, reciprocity included, so a steady
+// light gives the corrected time. `light` gives the EV at ISO 100 at a time in
+// s from the start, None for dark. None when it is not done by `limit` s.
+pub fn through(
+    light: impl Fn(f32) -> Option<f32>,
+    iso: f32,
+    n: f32,
+    film: Option<&Film>,
+    limit: f32,
+) -> Option<Fading> {
+    let needs = |ev: f32| for_metered(metered_time(ev, iso, n), film);
+    let mut at_start = None;
+    let (mut t, mut done) = (0f32, 0f32);
+    while t < limit {
+        let Some(ev) = light(t) else {
+            t += 1.;
+            continue;
+        };
+        let now = needs(ev);
+        let at_start = *at_start.get_or_insert(now);
+        let needed = now.corrected;
+        let dt = (needed / 200.).clamp(1e-3, 10.);
+        if done + dt / needed >= 1. {
+            return Some(Fading {
+                at_start,
+                duration: t + (1. - done) * needed,
+            });
+        }
+        done += dt / needed;
+        t += dt;
+    }
+    None
+}
+
+// Short, for tables: "1/4s", "0.8s", "12s", "3m 20s", "1h 05m".
+pub fn format_time(t: f32) -> String {
+    if t < 0.3 {
+        return format!("1/{:.0}s", 1. / t);
+    }
+    if t < 9.95 {
+        return format!("{t:.1}s");
+    }
+    let secs = t.round() as u64;
+    match secs {
+        0..60 => format!("{secs}s"),
+        60..3600 => format!("{}m {:02}s", secs / 60, secs % 60),
+        _ => {
+            let mins = (t / 60.).round() as u64;
+            format!("{}h {:02}m", mins / 60, mins % 60)
+        }
+    }
+}
+
+// A running clock, "4:05" or "1:02:03".
+pub fn format_clock(t: f32) -> String {
+    let secs = t.max(0.).ceil() as u64;
+    let (h, m, s) = (secs / 3600, secs / 60 % 60, secs % 60);
+    if h > 0 {
+        format!("{h}:{m:02}:{s:02}")
+    } else {
+        format!("{m}:{s:02}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sunny_sixteen() {
+        // EV 15 at ISO 100 is 1/125 at f/16, near enough.
+        let t = metered_time(15., 100., 16.);
+        assert!((1. / t - 128.).abs() < 1e-3);
+        assert!((ev(t, 100., 16.) - 15.).abs() < 1e-5);
+        assert!((metered_time(15., 400., 16.) - t / 4.).abs() < 1e-6);
+    }
+
+    #[test]
+    fn pinhole_with_reciprocity() {
+        let hp5 = crate::film::find("HP5 Plus").unwrap();
+        let e = at_pinhole(1. / 125., 16., 181., Some(hp5));
+        assert!((e.metered - 1.024).abs() < 0.01);
+        assert!(e.corrected > e.metered);
+        assert_eq!(at_pinhole(1. / 125., 16., 181., None).corrected, e.metered);
+        let e = for_ev(12., 181., hp5);
+        assert!((e.corrected - e.metered.powf(1.31)).abs() < 1e-3);
+        assert!(e.extra_stops() > 0.);
+        assert_eq!(for_metered(e.metered, Some(hp5)), e);
+        assert_eq!(for_metered(e.metered, None).corrected, e.metered);
+    }
+
+    #[test]
+    fn fading_light() {
+        let hp5 = crate::film::find("HP5 Plus").unwrap();
+        // Steady light gives the corrected time.
+        let steady = through(|_| Some(6.), 400., 180., Some(hp5), 3600.).unwrap();
+        assert!((steady.duration / steady.at_start.corrected - 1.).abs() < 0.01);
+        // Dusk, a stop less every five minutes, takes longer than the start says.
+        let points = [(0., 6.), (300., 5.), (600., 4.), (900., 3.)];
+        let dusk = through(from_points(&points), 400., 180., Some(hp5), 4. * 3600.).unwrap();
+        assert_eq!(dusk.at_start, steady.at_start);
+        assert!(dusk.duration > 1.2 * steady.duration, "{dusk:?} {steady:?}");
+        assert_eq!(from_points(&points)(450.), Some(4.5));
+        assert_eq!(from_points(&points)(2000.), Some(3.));
+        // Too dark to finish within the limit.
+        assert_eq!(through(|_| Some(-6.), 400., 180., Some(hp5), 3600.), None);
+        assert_eq!(through(|_| None, 400., 180., Some(hp5), 3600.), None);
+        // Started ten minutes before the light comes up, it starts from the
+        // first light and takes ten minutes longer than steady light.
+        let dawn = |t: f32| (t >= 600.).then_some(6.);
+        let dawn = through(dawn, 400., 180., Some(hp5), 3600.).unwrap();
+        assert_eq!(dawn.at_start, steady.at_start);
+        assert!(
+            (dawn.duration - 600. - steady.duration).abs() < 2.,
+            "{dawn:?}"
+        );
+    }
+
+    #[test]
+    fn times() {
+        assert_eq!(format_time(0.25), "1/4s");
+        assert_eq!(format_time(0.8), "0.8s");
+        assert_eq!(format_time(12.3), "12s");
+        assert_eq!(format_time(200.), "3m 20s");
+        assert_eq!(format_time(3900.), "1h 05m");
+        assert_eq!(format_clock(65.), "1:05");
+        assert_eq!(format_clock(3723.), "1:02:03");
+        assert_eq!(format_clock(0.2), "0:01");
+    }
+}
@@ -0,0 +1,179 @@
+// Relative illumination across the image. Light falls off with cos⁴ of the
+// field angle, and the plate cuts off more of it the further off axis: seen at
+// an angle the two edges of the hole overlap like a cat's eye, until nothing
+// gets through past calc_viewangle.
+use std::f32::consts::PI;
+
+use crate::formats::Format;
+use crate::sweep::Point;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Falloff {
+    pub radius: f32, // mm from the centre of the image
+    pub angle: f32,  // degrees off axis
+    pub cos4: f32,
+    pub thickness: f32,
+}
+
+impl Falloff {
+    pub fn total(&self) -> f32 {
+        self.cos4 * self.thickness
+    }
+
+    // Stops darker than the centre.
+    pub fn stops(&self) -> f32 {
+        crate::stop_equivalent(self.total())
+    }
+}
+
+// Share of the hole that is open seen at this angle (degrees), from 1 head on
+// to 0 at calc_viewangle.
+pub fn thickness_falloff(ph_diameter: f32, ph_thickness: f32, angle: f32) -> f32 {
+    let shift = ph_thickness * angle.to_radians().tan() / ph_diameter;
+    if shift >= 1. {
+        return 0.;
+    }
+    2. / PI * (shift.acos() - shift * (1. - shift * shift).sqrt())
+}
+
+pub fn at_angle(p: &Point, angle: f32) -> Falloff {
+    Falloff {
+        radius: p.focal_length * angle.to_radians().tan(),
+        angle,
+        cos4: angle.to_radians().cos().powi(4),
+        thickness: thickness_falloff(p.diameter, p.thickness, angle),
+    }
+}
+
+pub fn at_radius(p: &Point, radius: f32) -> Falloff {
+    Falloff {
+        radius,
+        ..at_angle(p, (radius / p.focal_length).atan().to_degrees())
+    }
+}
+
+// From the centre out to max_radius, in steps + 1 points.
+pub fn curve(p: &Point, max_radius: f32, steps: usize) -> Vec<Falloff> {
+    (0..=steps)
+        .map(|i| at_radius(p, max_radius * i as f32 / steps as f32))
+        .collect()
+}
+
+// Relative illumination on a grid of pixels, row by row from the top left.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Map {
+    pub width: usize,
+    pub height: usize,
+    pub values: Vec<f32>,
+}
+
+impl Map {
+    pub fn get(&self, x: usize, y: usize) -> f32 {
+        self.values[y * self.width + x]
+    }
+
+    // Lowest value, in the corner furthest from the centre.
+    pub fn min(&self) -> f32 {
+        self.values.iter().copied().fold(1., f32::min)
+    }
+}
+
+// The illumination on width x height pixels of `pixel` mm, with the optical
+// axis at `centre` (in pixels from the top left corner).
+pub fn grid(p: &Point, width: usize, height: usize, pixel: f32, centre: [f32; 2]) -> Map {
+    let mut values = Vec::with_capacity(width * height);
+    for y in 0..height {
+        for x in 0..width {
+            let dx = (x as f32 + 0.5 - centre[0]) * pixel;
+            let dy = (y as f32 + 0.5 - centre[1]) * pixel;
+            values.push(at_radius(p, dx.hypot(dy)).total());
+        }
+    }
+    Map {
+        width,
+        height,
+        values,
+    }
+}
+
+// The illumination over a format with the long side across, centred on the
+// axis, at `pixel` mm per pixel.
+pub fn map(p: &Point, format: &Format, pixel: f32) -> Map {
+    let [short, long, _] = format.radii();
+    let width = (2. * long / pixel).round().max(1.) as usize;
+    let height = (2. * short / pixel).round().max(1.) as usize;
+    grid(
+        p,
+        width,
+        height,
+        pixel,
+        [width as f32 / 2., height as f32 / 2.],
+    )
+}
+
+// Radius where the image is this many stops darker than the centre, found by
+// bisection out to where the plate cuts off all light.
+pub fn radius_at_stops(p: &Point, stops: f32) -> f32 {
+    let target = 2f32.powf(-stops);
+    let (mut lo, mut hi) = (0., p.coverage_radius());
+    for _ in 0..40 {
+        let mid = (lo + hi) / 2.;
+        if at_radius(p, mid).total() > target {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+    (lo + hi) / 2.
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn thickness_cuts_off_at_viewangle() {
+        assert_eq!(thickness_falloff(0.3, 0.04, 0.), 1.);
+        let cutoff = crate::calc_viewangle(0.3, 0.04);
+        assert!(thickness_falloff(0.3, 0.04, cutoff + 0.01) == 0.);
+        assert!(thickness_falloff(0.3, 0.04, cutoff - 1.) > 0.);
+    }
+
+    #[test]
+    fn cos4_matches_vignetting() {
+        let p = Point::default();
+        let f = at_radius(&p, p.projection / 2.);
+        assert!((f.cos4 - p.vignetting().0).abs() < 1e-6);
+        assert!((at_angle(&p, 45.).cos4 - 0.25).abs() < 1e-6);
+    }
+
+    #[test]
+    fn curve_runs_outwards() {
+        let c = curve(&Point::default(), 30., 10);
+        assert_eq!(c.len(), 11);
+        assert_eq!(c[0].total(), 1.);
+        assert_eq!(c[10].radius, 30.);
+        assert!(c.windows(2).all(|w| w[1].total() < w[0].total()));
+    }
+
+    #[test]
+    fn half_stop_radius() {
+        let p = Point::default();
+        let r = radius_at_stops(&p, 0.5);
+        assert!((at_radius(&p, r).stops() - 0.5).abs() < 1e-3);
+    }
+
+    #[test]
+    fn map_over_format() {
+        let p = Point::default();
+        let format = crate::formats::find("35mm").unwrap();
+        let m = map(&p, format, 0.5);
+        assert_eq!((m.width, m.height), (72, 48));
+        // Symmetric, brightest in the middle and darkest in the corners.
+        assert_eq!(m.get(0, 0), m.get(71, 47));
+        assert!(m.get(36, 24) > 0.99);
+        assert_eq!(m.min(), m.get(0, 0));
+        let corner = at_radius(&p, 0.5 * 35.5f32.hypot(23.5)).total();
+        assert!((m.get(0, 0) - corner).abs() < 1e-6);
+    }
+}
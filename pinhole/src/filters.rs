@@ -0,0 +1,316 @@
+// Filters in front of the pinhole: their factors for panchromatic film in
+// daylight and, for the coloured ones, rough transmission curves after the
+// Wratten data sheets. A stack of them lengthens the exposure and, by taking
+// out part of the spectrum, moves the wavelength the pinhole is sized for.
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Filter {
+    pub name: &'static str,
+    // Exposure × this.
+    pub factor: f32,
+    // Wavelength in nm and transmission, straight lines in between and the
+    // end values past the ends. None for neutral density.
+    pub curve: Option<&'static [(f32, f32)]>,
+}
+
+impl Filter {
+    pub fn stops(&self) -> f32 {
+        self.factor.log2()
+    }
+
+    pub fn transmission(&self, nm: f32) -> f32 {
+        let Some(points) = self.curve else {
+            return 1. / self.factor;
+        };
+        match points {
+            [] => 1.,
+            [(first, t), ..] if nm <= *first => *t,
+            [.., (last, t)] if nm >= *last => *t,
+            _ => {
+                let i = points.iter().position(|(w, _)| *w >= nm).unwrap_or(1);
+                let ((w0, t0), (w1, t1)) = (points[i - 1], points[i]);
+                t0 + (t1 - t0) * (nm - w0) / (w1 - w0)
+            }
+        }
+    }
+}
+
+const fn filter(name: &'static str, factor: f32, curve: Option<&'static [(f32, f32)]>) -> Filter {
+    Filter {
+        name,
+        factor,
+        curve,
+    }
+}
+
+pub const FILTERS: [Filter; 12] = [
+    filter("ND2", 2., None),
+    filter("ND4", 4., None),
+    filter("ND8", 8., None),
+    filter("ND64", 64., None),
+    filter("ND1000", 1000., None),
+    filter(
+        "Yellow 8",
+        2.,
+        Some(&[
+            (400., 0.02),
+            (460., 0.05),
+            (480., 0.3),
+            (500., 0.75),
+            (520., 0.88),
+            (700., 0.9),
+        ]),
+    ),
+    filter(
+        "Orange 21",
+        4.,
+        Some(&[
+            (500., 0.),
+            (540., 0.05),
+            (560., 0.5),
+            (580., 0.85),
+            (700., 0.9),
+        ]),
+    ),
+    filter(
+        "Red 25",
+        8.,
+        Some(&[
+            (560., 0.),
+            (580., 0.05),
+            (600., 0.5),
+            (620., 0.85),
+            (700., 0.9),
+        ]),
+    ),
+    filter(
+        "Deep red 29",
+        16.,
+        Some(&[
+            (590., 0.),
+            (610., 0.05),
+            (630., 0.5),
+            (650., 0.85),
+            (700., 0.9),
+        ]),
+    ),
+    filter(
+        "Green 11",
+        4.,
+        Some(&[
+            (400., 0.05),
+            (460., 0.3),
+            (520., 0.65),
+            (560., 0.6),
+            (620., 0.1),
+            (660., 0.02),
+            (700., 0.1),
+            (740., 0.6),
+        ]),
+    ),
+    filter(
+        "Blue 80A",
+        4.,
+        Some(&[
+            (400., 0.7),
+            (450., 0.75),
+            (500., 0.6),
+            (550., 0.35),
+            (600., 0.15),
+            (650., 0.1),
+            (700., 0.2),
+            (750., 0.6),
+        ]),
+    ),
+    filter(
+        "IR R72",
+        32.,
+        Some(&[
+            (680., 0.),
+            (700., 0.05),
+            (720., 0.5),
+            (740., 0.85),
+            (900., 0.9),
+        ]),
+    ),
+];
+
+// By name, ignoring case, spaces and '#', "red #25" finds "Red 25".
+pub fn find(name: &str) -> Option<&'static Filter> {
+    let squash = |s: &str| s.replace([' ', '#'], "").to_lowercase();
+    let wanted = squash(name);
+    if wanted.is_empty() {
+        return None;
+    }
+    FILTERS
+        .iter()
+        .find(|f| squash(f.name) == wanted)
+        .or_else(|| FILTERS.iter().find(|f| squash(f.name).ends_with(&wanted)))
+}
+
+// Filters on top of each other, their factors multiply.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Stack {
+    pub filters: Vec<&'static Filter>,
+}
+
+// Panchromatic film, even from 400 to 700 nm and gone by 750.
+fn sensitivity(nm: f32) -> f32 {
+    ((750. - nm) / 50.).clamp(0., 1.)
+}
+
+const SPECTRUM: std::ops::RangeInclusive<u32> = 40..=90;
+
+impl Stack {
+    pub fn is_empty(&self) -> bool {
+        self.filters.is_empty()
+    }
+
+    pub fn factor(&self) -> f32 {
+        self.filters.iter().map(|f| f.factor).product()
+    }
+
+    pub fn stops(&self) -> f32 {
+        self.filters.iter().map(|f| f.stops()).sum()
+    }
+
+    // A reading taken without the filters, as it is behind them. Pass these
+    // to exposure::for_ev or at_pinhole so reciprocity works on the longer
+    // time.
+    pub fn ev(&self, ev: f32) -> f32 {
+        ev - self.stops()
+    }
+
+    pub fn time(&self, metered: f32) -> f32 {
+        metered * self.factor()
+    }
+
+    pub fn transmission(&self, nm: f32) -> f32 {
+        self.filters.iter().map(|f| f.transmission(nm)).product()
+    }
+
+    // The wavelength to size the pinhole for, `base` moved by as much as the
+    // filters move the middle of what the film sees. When they leave the
+    // film nothing, as an IR filter on film that stops at 750 nm, by the
+    // middle of what gets through. Filters that let nothing through at all
+    // leave it.
+    pub fn wavelength(&self, base: f32) -> f32 {
+        if self.filters.iter().all(|f| f.curve.is_none()) {
+            return base;
+        }
+        let middle = |weight: &dyn Fn(f32) -> f32| {
+            let (mut sum, mut total) = (0., 0.);
+            for nm in SPECTRUM.map(|w| w as f32 * 10.) {
+                sum += nm * weight(nm);
+                total += weight(nm);
+            }
+            (sum / total, total)
+        };
+        let (open, seen) = middle(&sensitivity);
+        let (filtered, left) = middle(&|nm| sensitivity(nm) * self.transmission(nm));
+        if left > 1e-3 * seen {
+            return base + filtered - open;
+        }
+        match middle(&|nm| self.transmission(nm)) {
+            (through, all) if all > 0. => base + through - open,
+            _ => base,
+        }
+    }
+}
+
+// "Red 25 + ND8"
+impl fmt::Display for Stack {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, filter) in self.filters.iter().enumerate() {
+            if i > 0 {
+                write!(f, " + ")?;
+            }
+            write!(f, "{}", filter.name)?;
+        }
+        Ok(())
+    }
+}
+
+// The filters that are known and the names that are not, for designs that
+// should still show with a misspelt filter.
+pub fn known<S: AsRef<str>>(names: &[S]) -> (Stack, Vec<String>) {
+    let mut stack = Stack::default();
+    let mut unknown = Vec::new();
+    for name in names {
+        match find(name.as_ref()) {
+            Some(f) => stack.filters.push(f),
+            None => unknown.push(name.as_ref().to_string()),
+        }
+    }
+    (stack, unknown)
+}
+
+pub fn stack<S: AsRef<str>>(names: &[S]) -> Result<Stack, String> {
+    let filters = names
+        .iter()
+        .map(|name| {
+            let name = name.as_ref();
+            find(name).ok_or_else(|| {
+                let names: Vec<_> = FILTERS.iter().map(|f| f.name).collect();
+                format!("unknown filter '{name}', one of: {}", names.join(", "))
+            })
+        })
+        .collect::<Result<_, _>>()?;
+    Ok(Stack { filters })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exposure;
+
+    #[test]
+    fn stops_add_up() {
+        let s = stack(&["red #25", "nd8"]).unwrap();
+        assert_eq!(s.to_string(), "Red 25 + ND8");
+        assert_eq!(s.factor(), 64.);
+        assert!((s.stops() - 6.).abs() < 1e-5);
+        assert!((find("ND1000").unwrap().stops() - 9.97).abs() < 0.01);
+        assert!(stack(&["Polariser"]).unwrap_err().contains("ND2"));
+        let (some, unknown) = known(&["ND1000", "Polariser"]);
+        assert_eq!(some.to_string(), "ND1000");
+        assert_eq!(unknown, ["Polariser"]);
+        assert!(Stack::default().is_empty());
+        assert_eq!(Stack::default().factor(), 1.);
+        // The filters come before reciprocity: a reading through them is
+        // the metered time × 64, corrected after.
+        let hp5 = crate::film::find("HP5 Plus").unwrap();
+        let open = exposure::for_ev(12., 180., hp5);
+        let e = exposure::for_ev(s.ev(12.), 180., hp5);
+        assert!((e.metered / open.metered - 64.).abs() < 0.01);
+        assert!((e.corrected - e.metered.powf(1.31)).abs() / e.corrected < 1e-3);
+        let at = exposure::at_pinhole(s.time(1. / 125.), 16., 180., Some(hp5));
+        assert!(
+            (at.metered - exposure::at_pinhole(1. / 125., 16., 180., None).metered * 64.).abs()
+                < 0.1
+        );
+    }
+
+    #[test]
+    fn colour_moves_the_wavelength() {
+        let nd = stack(&["ND64"]).unwrap();
+        assert_eq!(nd.wavelength(550.), 550.);
+        let red = stack(&["Red 25"]).unwrap();
+        let yellow = stack(&["Yellow 8"]).unwrap();
+        let blue = stack(&["Blue 80A"]).unwrap();
+        assert!(red.wavelength(550.) > yellow.wavelength(550.));
+        assert!(yellow.wavelength(550.) > 550.);
+        assert!(blue.wavelength(550.) < 550.);
+        assert!((red.transmission(590.) - 0.275).abs() < 1e-4);
+        assert_eq!(red.transmission(300.), 0.);
+        // Red on IR lets only the deep red through, further out again.
+        let ir = stack(&["IR R72"]).unwrap();
+        assert!(ir.wavelength(550.) > red.wavelength(550.) + 50.);
+        let none = stack(&["Blue 80A", "IR R72", "Red 25"]).unwrap();
+        assert!(none.wavelength(550.).is_finite());
+        // A longer wavelength wants a bigger pinhole.
+        let open = crate::calc_optimalsize(50., 550., 1.56, 0.);
+        assert!(crate::calc_optimalsize(50., red.wavelength(550.), 1.56, 0.) > open);
+    }
+}
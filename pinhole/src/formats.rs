@@ -0,0 +1,147 @@
+use std::fmt;
+
+// Film formats, sizes in mm.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Format {
+    pub name: &'static str,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl Format {
+    // Diameter of the image circle needed to cover the format.
+    pub fn projection_diameter(&self) -> f32 {
+        crate::projection_diameter(self.width, self.height)
+    }
+
+    // Distance from the centre to the nearest edge (half the short side), the
+    // farthest edge (half the long side) and the corner.
+    pub fn radii(&self) -> [f32; 3] {
+        let (short, long) = if self.width < self.height {
+            (self.width, self.height)
+        } else {
+            (self.height, self.width)
+        };
+        [
+            short / 2.,
+            long / 2.,
+            (short * short + long * long).sqrt() / 2.,
+        ]
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+const INCH: f32 = 25.4;
+
+pub const FORMATS: [Format; 13] = [
+    Format {
+        name: "35mm",
+        width: 24.,
+        height: 36.,
+    },
+    Format {
+        name: "645",
+        width: 60.,
+        height: 45.,
+    },
+    Format {
+        name: "6x6",
+        width: 60.,
+        height: 60.,
+    },
+    Format {
+        name: "6x7",
+        width: 60.,
+        height: 70.,
+    },
+    Format {
+        name: "6x9",
+        width: 60.,
+        height: 90.,
+    },
+    Format {
+        name: "6x12",
+        width: 60.,
+        height: 120.,
+    },
+    Format {
+        name: "4\"x5\"",
+        width: 4. * INCH,
+        height: 5. * INCH,
+    },
+    Format {
+        name: "6x17",
+        width: 60.,
+        height: 170.,
+    },
+    Format {
+        name: "5\"x7\"",
+        width: 5. * INCH,
+        height: 7. * INCH,
+    },
+    Format {
+        name: "8\"x10\"",
+        width: 8. * INCH,
+        height: 10. * INCH,
+    },
+    Format {
+        name: "11\"x14\"",
+        width: 11. * INCH,
+        height: 14. * INCH,
+    },
+    Format {
+        name: "16\"x20\"",
+        width: 16. * INCH,
+        height: 20. * INCH,
+    },
+    Format {
+        name: "20\"x24\"",
+        width: 20. * INCH,
+        height: 24. * INCH,
+    },
+];
+
+// Look up a format by name. The inch marks are optional, so "4x5" finds 4"x5".
+pub fn find(name: &str) -> Option<&'static Format> {
+    let wanted = name.replace('"', "").to_lowercase();
+    FORMATS
+        .iter()
+        .find(|f| f.name.replace('"', "").to_lowercase() == wanted)
+}
+
+// The format a projection diameter was chosen for, if any.
+pub fn from_diameter(diameter: f32) -> Option<&'static Format> {
+    FORMATS
+        .iter()
+        .find(|f| (f.projection_diameter() - diameter).abs() < 1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn find_without_inch_marks() {
+        let f = find("4x5").unwrap();
+        assert_eq!(f.name, "4\"x5\"");
+    }
+
+    #[test]
+    fn radii() {
+        let f = find("6x9").unwrap();
+        assert_eq!(f.radii()[..2], [30., 45.]);
+        assert!((f.radii()[2] - 54.08).abs() < 0.01);
+    }
+
+    #[test]
+    fn diameter_roundtrip() {
+        for f in FORMATS.iter() {
+            assert_eq!(from_diameter(f.projection_diameter()), Some(f));
+        }
+    }
+}
@@ -0,0 +1,177 @@
+// Vignetting profiles for lensfun, read by darktable and RawTherapee. Lensfun's
+// "pa" model has the illumination as 1 + k1·r² + k2·r⁴ + k3·r⁶, with r from 0
+// in the centre to 1 in the corner of the format.
+use std::fmt::Write;
+
+use crate::design::Design;
+use crate::formats::Format;
+use crate::sweep::Point;
+
+// Diagonal of 35mm film, lensfun's crop factor 1.
+pub(crate) const FULL_FRAME_DIAGONAL: f32 = 43.267;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Vignetting {
+    pub k1: f32,
+    pub k2: f32,
+    pub k3: f32,
+}
+
+impl Vignetting {
+    pub fn at(&self, r: f32) -> f32 {
+        let x = r * r;
+        1. + x * (self.k1 + x * (self.k2 + x * self.k3))
+    }
+}
+
+// Least squares fit of the predicted falloff, cos⁴ and the plate together,
+// from the centre to the corner of the format.
+pub fn fit(p: &Point, format: &Format) -> Vignetting {
+    let corner = format.radii()[2] as f64;
+    let mut ata = [[0f64; 3]; 3];
+    let mut atb = [0f64; 3];
+    for i in 0..=100 {
+        let r = i as f64 / 100.;
+        let y = crate::falloff::at_radius(p, (r * corner) as f32).total() as f64 - 1.;
+        let x = r * r;
+        let row = [x, x * x, x * x * x];
+        for j in 0..3 {
+            atb[j] += row[j] * y;
+            for k in 0..3 {
+                ata[j][k] += row[j] * row[k];
+            }
+        }
+    }
+    let [k1, k2, k3] = solve(ata, atb);
+    Vignetting {
+        k1: k1 as f32,
+        k2: k2 as f32,
+        k3: k3 as f32,
+    }
+}
+
+// Gaussian elimination with partial pivoting, the normal equations are small
+// and well behaved.
+fn solve(mut a: [[f64; 3]; 3], mut b: [f64; 3]) -> [f64; 3] {
+    for col in 0..3 {
+        let pivot = (col..3)
+            .max_by(|&i, &j| a[i][col].abs().total_cmp(&a[j][col].abs()))
+            .unwrap_or(col);
+        a.swap(col, pivot);
+        b.swap(col, pivot);
+        let top = a[col];
+        for row in col + 1..3 {
+            let f = a[row][col] / top[col];
+            for (v, t) in a[row].iter_mut().zip(top).skip(col) {
+                *v -= f * t;
+            }
+            b[row] -= f * b[col];
+        }
+    }
+    let mut x = [0f64; 3];
+    for row in (0..3).rev() {
+        let rest: f64 = (row + 1..3).map(|k| a[row][k] * x[k]).sum();
+        x[row] = (b[row] - rest) / a[row][row];
+    }
+    x
+}
+
+pub fn crop_factor(format: &Format) -> f32 {
+    FULL_FRAME_DIAGONAL / (2. * format.radii()[2])
+}
+
+// A lens name for the design, "Pinhole 50mm f/167" without one.
+pub fn model(design: &Design) -> String {
+    let p = design.point();
+    let name = if design.name.is_empty() {
+        "Pinhole"
+    } else {
+        &design.name
+    };
+    format!("{name} {:.0}mm f/{:.0}", p.focal_length, p.fnumber())
+}
+
+// A complete lensfun database with one <lens> for the design.
+pub fn to_xml(design: &Design, format: &Format, maker: &str, mount: &str) -> String {
+    let p = design.point();
+    let v = fit(&p, format);
+    // Lensfun wants a focus distance in m, far away is as good as infinity.
+    let distance = p.subject_distance.min(1000.);
+    let mut xml = String::new();
+    let _ = writeln!(xml, "<lensdatabase version=\"2\">");
+    let _ = writeln!(xml, "    <lens>");
+    let _ = writeln!(xml, "        <maker>{}</maker>", escape(maker));
+    let _ = writeln!(xml, "        <model>{}</model>", escape(&model(design)));
+    let _ = writeln!(xml, "        <mount>{}</mount>", escape(mount));
+    let _ = writeln!(
+        xml,
+        "        <cropfactor>{:.3}</cropfactor>",
+        crop_factor(format)
+    );
+    let _ = writeln!(xml, "        <focal value=\"{:.1}\"/>", p.focal_length);
+    let _ = writeln!(
+        xml,
+        "        <aperture min=\"{0:.1}\" max=\"{0:.1}\"/>",
+        p.fnumber()
+    );
+    let _ = writeln!(xml, "        <calibration>");
+    let _ = writeln!(
+        xml,
+        "            <vignetting model=\"pa\" focal=\"{:.1}\" aperture=\"{:.1}\" distance=\"{}\" k1=\"{:.5}\" k2=\"{:.5}\" k3=\"{:.5}\"/>",
+        p.focal_length,
+        p.fnumber(),
+        distance,
+        v.k1,
+        v.k2,
+        v.k3
+    );
+    let _ = writeln!(xml, "        </calibration>");
+    let _ = writeln!(xml, "    </lens>");
+    let _ = writeln!(xml, "</lensdatabase>");
+    xml
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn zero() -> Design {
+        Design {
+            name: "Zero <2000>".to_string(),
+            focal_length: 25.,
+            diameter: 0.18,
+            thickness: 0.05,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn fit_follows_falloff() {
+        let p = zero().point();
+        let format = crate::formats::find("35mm").unwrap();
+        let v = fit(&p, format);
+        let corner = format.radii()[2];
+        // The plate's loss starts linear in r, the even polynomial only comes
+        // close to it.
+        for r in [0., 0.3, 0.6, 0.9, 1.] {
+            let expected = crate::falloff::at_radius(&p, r * corner).total();
+            assert!((v.at(r) - expected).abs() < 0.03, "at {r}");
+        }
+    }
+
+    #[test]
+    fn lens_entry() {
+        let format = crate::formats::find("35mm").unwrap();
+        let xml = to_xml(&zero(), format, "Zero Image", "Generic");
+        assert!(xml.contains("<model>Zero &lt;2000&gt; 25mm f/139</model>"));
+        assert!(xml.contains("<cropfactor>1.000</cropfactor>"));
+        assert!(xml.contains("model=\"pa\" focal=\"25.0\" aperture=\"138.9\" distance=\"1000\""));
+    }
+}
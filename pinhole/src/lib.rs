@@ -1,3 +1,21 @@
+pub mod closeup;
+pub mod design;
+pub mod exposure;
+pub mod falloff;
+pub mod film;
+pub mod filters;
+pub mod formats;
+pub mod lensfun;
+pub mod library;
+pub mod logbook;
+pub mod metadata;
+pub mod scenes;
+pub mod sharpness;
+pub mod solargraphy;
+pub mod stops;
+pub mod sun;
+pub mod sweep;
+
 pub fn calc_optimalsize(
     ph_focallength: f32, // mm
     ph_wavelength: f32,  // nm * 1e6
@@ -25,15 +43,24 @@ pub fn calc_vignetting(ph_focallength: f32, film_radius: f32) -> (f32, f32) {
     (cos4, angle)
 }
 
+// Effective f-number of the pinhole.
+pub fn fnumber(ph_focallength: f32, ph_diameter: f32) -> f32 {
+    ph_focallength / ph_diameter
+}
+
+// Exposure needs to be multiplied by this going from fstop0 to fstop1.
+pub fn exposure_factor(fstop0: f32, fstop1: f32) -> f32 {
+    (fstop1 / fstop0).powi(2)
+}
+
 // 0.56 (eg from cos^4) is 0.84 stops darker.
 pub fn stop_equivalent(fract: f32) -> f32 {
     -fract.log2()
 }
-//
-// Decimal part * 3 will give a "thirds of a stop" approximation.
-// Factor was 6. for 1/3rd stops, now we return stops.
+
+// Stops from fstop0 to fstop1, despite the name; stops::thirds rounds them.
 pub fn delta_thirds(fstop0: f32, fstop1: f32) -> f32 {
-    2. * (fstop1 / fstop0).log2() // multiply by 6 for 1/3rd stops.
+    stops::between_fnumbers(fstop0, fstop1)
 }
 
 // Coverage for a given focal length with a given view angle.
@@ -90,6 +117,18 @@ mod tests {
         assert_eq!(diff, 4.);
     }
 
+    #[test]
+    fn f_number() {
+        let n = fnumber(50., 0.25);
+        assert_eq!(n, 200.);
+    }
+
+    #[test]
+    fn two_stops_factor() {
+        let f = exposure_factor(32., 64.);
+        assert_eq!(f, 4.);
+    }
+
     #[test]
     fn coverage() {
         let cr = coverage_radius(50., 45.);
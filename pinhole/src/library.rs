@@ -0,0 +1,138 @@
+// A set of named designs kept together in one TOML file, one [[design]]
+// table each.
+use std::fs;
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::design::{Design, DesignError, VERSION};
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Library {
+    pub designs: Vec<Design>,
+}
+
+#[derive(Serialize)]
+struct LibraryFile<'a> {
+    version: i64,
+    design: &'a [Design],
+}
+
+impl Library {
+    // Replaces the design with the same name, or adds it at the end.
+    pub fn insert(&mut self, design: Design) -> usize {
+        match self.designs.iter().position(|d| d.name == design.name) {
+            Some(i) => {
+                self.designs[i] = design;
+                i
+            }
+            None => {
+                self.designs.push(design);
+                self.designs.len() - 1
+            }
+        }
+    }
+
+    pub fn remove(&mut self, index: usize) -> Design {
+        self.designs.remove(index)
+    }
+
+    pub fn to_toml(&self) -> Result<String, DesignError> {
+        let file = LibraryFile {
+            version: VERSION,
+            design: &self.designs,
+        };
+        toml::to_string(&file).map_err(|e| DesignError::Parse(e.to_string()))
+    }
+
+    // Every design goes through the same migration as a single design file.
+    pub fn from_toml(s: &str) -> Result<Library, DesignError> {
+        let mut table: toml::Table =
+            toml::from_str(s).map_err(|e| DesignError::Parse(e.to_string()))?;
+        let version = table.get("version").and_then(|v| v.as_integer());
+        let designs = match table.remove("design") {
+            Some(toml::Value::Array(designs)) => designs,
+            Some(_) => return Err(DesignError::Parse("design is not a list".to_string())),
+            None => Vec::new(),
+        };
+        let designs = designs
+            .into_iter()
+            .map(|d| {
+                let mut value =
+                    serde_json::to_value(d).map_err(|e| DesignError::Parse(e.to_string()))?;
+                // The designs inherit the version of the file they are in.
+                if let (Some(v), Some(table)) = (version, value.as_object_mut()) {
+                    table.entry("version").or_insert(v.into());
+                }
+                Design::from_value(value)
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Library { designs })
+    }
+
+    // An empty library when the file is not there yet.
+    pub fn load(path: &Path) -> Result<Library, DesignError> {
+        match fs::read_to_string(path) {
+            Ok(s) => Library::from_toml(&s),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Library::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), DesignError> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        crate::design::write_replacing(path, &self.to_toml()?)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn named(name: &str, focal_length: f32) -> Design {
+        Design {
+            name: name.to_string(),
+            focal_length,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn insert_replaces_same_name() {
+        let mut lib = Library::default();
+        lib.insert(named("Zero 2000", 25.));
+        lib.insert(named("4x5 box", 90.));
+        assert_eq!(lib.insert(named("Zero 2000", 26.)), 0);
+        assert_eq!(lib.designs.len(), 2);
+        assert_eq!(lib.designs[0].focal_length, 26.);
+    }
+
+    #[test]
+    fn toml_roundtrip() {
+        let mut lib = Library::default();
+        lib.insert(named("Zero 2000", 25.));
+        lib.insert(Design {
+            subject_distance: 3.,
+            ..named("6x17 curved", 40.)
+        });
+        let back = Library::from_toml(&lib.to_toml().unwrap()).unwrap();
+        assert_eq!(back, lib);
+    }
+
+    #[test]
+    fn save_replaces_the_file() {
+        let dir = std::env::temp_dir().join("pinhole-library-test");
+        let path = dir.join("library.toml");
+        let mut lib = Library::default();
+        lib.insert(named("Zero 2000", 25.));
+        lib.save(&path).unwrap();
+        lib.insert(named("4x5 box", 90.));
+        lib.save(&path).unwrap();
+        assert_eq!(Library::load(&path).unwrap(), lib);
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
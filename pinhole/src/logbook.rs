@@ -0,0 +1,336 @@
+// A notebook of the shots taken, one JSON object per line so a new shot is
+// only appended. Each shot keeps the whole design it was taken with.
+use std::fmt::Write as _;
+use std::fs;
+use std::io::Write as _;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::design::{Design, DesignError};
+use crate::exposure::{self, Exposure, format_time};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Shot {
+    // Local date and time, "2026-10-18 14:05".
+    pub taken: String,
+    pub design: Design,
+    pub film: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ev: Option<f32>,
+    // What the calculator said, reciprocity included.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exposure: Option<Exposure>,
+    // Seconds the shutter was open.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub actual: Option<f32>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub location: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub notes: String,
+}
+
+impl Shot {
+    // The exposure for an EV reading through the design's filters, when the
+    // film and filters are ones we know.
+    pub fn expected(design: &Design, film: &str, ev: f32) -> Option<Exposure> {
+        let film = crate::film::find(film)?;
+        let stack = design.stack().ok()?;
+        Some(exposure::for_ev(
+            stack.ev(ev),
+            design.point().fnumber(),
+            film,
+        ))
+    }
+
+    pub fn to_json(&self) -> Result<String, DesignError> {
+        serde_json::to_string(self).map_err(|e| DesignError::Parse(e.to_string()))
+    }
+
+    // The design goes through the same migration as a design file.
+    pub fn from_json(s: &str) -> Result<Shot, DesignError> {
+        let mut value: serde_json::Value =
+            serde_json::from_str(s).map_err(|e| DesignError::Parse(e.to_string()))?;
+        let Some(table) = value.as_object_mut() else {
+            return Err(DesignError::Parse("expected an object".to_string()));
+        };
+        let design = Design::from_value(table.remove("design").unwrap_or_default())?;
+        let design = serde_json::to_value(design).map_err(|e| DesignError::Parse(e.to_string()))?;
+        table.insert("design".to_string(), design);
+        serde_json::from_value(value).map_err(|e| DesignError::Parse(e.to_string()))
+    }
+
+    // Every word of the query somewhere in the shot, ignoring case.
+    pub fn matches(&self, query: &str) -> bool {
+        let text = [
+            self.taken.as_str(),
+            &self.design.name,
+            &self.film,
+            &self.location,
+            &self.notes,
+        ]
+        .join(" ")
+        .to_lowercase();
+        query
+            .to_lowercase()
+            .split_whitespace()
+            .all(|word| text.contains(word))
+    }
+}
+
+// A line that does not read as a shot, edited by hand or written by a newer
+// version. Kept as it is so rewriting the file does not lose it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Unreadable {
+    pub line: usize,
+    pub text: String,
+    pub error: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Logbook {
+    pub shots: Vec<Shot>,
+    pub unreadable: Vec<Unreadable>,
+}
+
+impl Logbook {
+    // Lines that do not read go to `unreadable` instead of failing the lot.
+    pub fn from_jsonl(s: &str) -> Logbook {
+        let mut book = Logbook::default();
+        for (i, line) in s.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            match Shot::from_json(line) {
+                Ok(shot) => book.shots.push(shot),
+                Err(e) => book.unreadable.push(Unreadable {
+                    line: i + 1,
+                    text: line.to_string(),
+                    error: e.to_string(),
+                }),
+            }
+        }
+        book
+    }
+
+    // The unreadable lines go at the end.
+    pub fn to_jsonl(&self) -> Result<String, DesignError> {
+        let mut s = String::new();
+        for shot in &self.shots {
+            s.push_str(&shot.to_json()?);
+            s.push('\n');
+        }
+        for u in &self.unreadable {
+            s.push_str(&u.text);
+            s.push('\n');
+        }
+        Ok(s)
+    }
+
+    // An empty logbook when the file is not there yet.
+    pub fn load(path: &Path) -> Result<Logbook, DesignError> {
+        match fs::read_to_string(path) {
+            Ok(s) => Ok(Logbook::from_jsonl(&s)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Logbook::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    // Rewrites the file, after a shot was removed.
+    pub fn save(&self, path: &Path) -> Result<(), DesignError> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        crate::design::write_replacing(path, &self.to_jsonl()?)?;
+        Ok(())
+    }
+
+    // Adds a shot and writes just that line.
+    pub fn append(&mut self, path: &Path, shot: Shot) -> Result<(), DesignError> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let line = shot.to_json()?;
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?;
+        writeln!(file, "{line}")?;
+        self.shots.push(shot);
+        Ok(())
+    }
+
+    pub fn filter(&self, query: &str) -> Vec<&Shot> {
+        self.shots.iter().filter(|s| s.matches(query)).collect()
+    }
+}
+
+const HEADINGS: [&str; 12] = [
+    "Taken",
+    "Camera",
+    "Focal length (mm)",
+    "Pinhole (mm)",
+    "F-number",
+    "Film",
+    "EV",
+    "Metered (s)",
+    "Computed (s)",
+    "Actual (s)",
+    "Location",
+    "Notes",
+];
+
+fn fields(shot: &Shot) -> [String; 12] {
+    let number =
+        |v: Option<f32>, decimals: usize| v.map_or(String::new(), |v| format!("{v:.decimals$}"));
+    let p = shot.design.point();
+    [
+        shot.taken.clone(),
+        shot.design.name.clone(),
+        format!("{:.1}", p.focal_length),
+        format!("{:.2}", p.diameter),
+        format!("{:.0}", p.fnumber()),
+        shot.film.clone(),
+        number(shot.ev, 1),
+        number(shot.exposure.map(|e| e.metered), 1),
+        number(shot.exposure.map(|e| e.corrected), 1),
+        number(shot.actual, 1),
+        shot.location.clone(),
+        shot.notes.clone(),
+    ]
+}
+
+pub fn to_csv(shots: &[&Shot]) -> String {
+    let quote = |s: &str| {
+        if s.contains([',', '"', '\n']) {
+            format!("\"{}\"", s.replace('"', "\"\""))
+        } else {
+            s.to_string()
+        }
+    };
+    let mut csv = HEADINGS.join(",") + "\n";
+    for shot in shots {
+        let row: Vec<_> = fields(shot).iter().map(|f| quote(f)).collect();
+        let _ = writeln!(csv, "{}", row.join(","));
+    }
+    csv
+}
+
+// A table with the times written out, for notes and forum posts.
+pub fn to_markdown(shots: &[&Shot]) -> String {
+    let cell = |s: &str| s.replace('|', "\\|").replace('\n', " ");
+    let headings = [
+        "Taken", "Camera", "F-number", "Film", "EV", "Computed", "Actual", "Location", "Notes",
+    ];
+    let mut md = format!("| {} |\n", headings.join(" | "));
+    let _ = writeln!(md, "|{}", "---|".repeat(headings.len()));
+    for shot in shots {
+        let time = |t: Option<f32>| t.map_or(String::new(), format_time);
+        let row = [
+            cell(&shot.taken),
+            cell(&shot.design.name),
+            format!("f/{:.0}", shot.design.point().fnumber()),
+            cell(&shot.film),
+            shot.ev.map_or(String::new(), |ev| format!("{ev:.1}")),
+            time(shot.exposure.map(|e| e.corrected)),
+            time(shot.actual),
+            cell(&shot.location),
+            cell(&shot.notes),
+        ];
+        let _ = writeln!(md, "| {} |", row.join(" | "));
+    }
+    md
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shot() -> Shot {
+        let design = Design {
+            name: "Zero, 6x9".to_string(),
+            focal_length: 40.,
+            diameter: 0.25,
+            ..Default::default()
+        };
+        Shot {
+            taken: "2026-10-18 14:05".to_string(),
+            exposure: Shot::expected(&design, "HP5 Plus", 12.),
+            design,
+            film: "Ilford HP5 Plus".to_string(),
+            ev: Some(12.),
+            actual: Some(2.5),
+            location: "Pier | west end".to_string(),
+            notes: String::new(),
+        }
+    }
+
+    #[test]
+    fn jsonl_roundtrip() {
+        let book = Logbook {
+            shots: vec![shot(), shot()],
+            ..Default::default()
+        };
+        let s = book.to_jsonl().unwrap();
+        assert_eq!(s.lines().count(), 2);
+        assert_eq!(Logbook::from_jsonl(&s), book);
+        // A bad line is reported by number and written back as it was.
+        let newer = r#"{"taken": "2030-01-01 10:00", "design": {"version": 99}, "film": "x"}"#;
+        let bad = Logbook::from_jsonl(&format!("{{\n{s}\n{newer}"));
+        assert_eq!(bad.shots, book.shots);
+        let lines: Vec<_> = bad.unreadable.iter().map(|u| u.line).collect();
+        assert_eq!(lines, [1, 5]);
+        assert!(
+            bad.unreadable[1].error.contains("99"),
+            "{:?}",
+            bad.unreadable
+        );
+        let back = bad.to_jsonl().unwrap();
+        assert_eq!(back.lines().count(), 4);
+        assert!(back.ends_with(&format!("{{\n{newer}\n")));
+    }
+
+    #[test]
+    fn save_after_removing() {
+        let dir = std::env::temp_dir().join("pinhole-logbook-test");
+        let path = dir.join("logbook.jsonl");
+        let _ = fs::remove_dir_all(&dir);
+        let mut book = Logbook::default();
+        book.append(&path, shot()).unwrap();
+        book.append(&path, shot()).unwrap();
+        book.shots.pop();
+        book.save(&path).unwrap();
+        assert_eq!(Logbook::load(&path).unwrap(), book);
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn old_designs_migrate() {
+        let line = r#"{"taken": "2026-01-01 10:00", "design": {"focal_length": 50}, "film": "x"}"#;
+        let shot = Shot::from_json(line).unwrap();
+        assert_eq!(shot.design.version, crate::design::VERSION);
+        assert_eq!(shot.design.focal_length, 50.);
+        assert!(shot.exposure.is_none());
+    }
+
+    #[test]
+    fn filter_and_export() {
+        let book = Logbook {
+            shots: vec![shot()],
+            ..Default::default()
+        };
+        assert_eq!(book.filter("hp5 2026-10").len(), 1);
+        assert!(book.filter("fomapan").is_empty());
+        let csv = to_csv(&book.filter(""));
+        assert!(
+            csv.lines()
+                .nth(1)
+                .unwrap()
+                .starts_with("2026-10-18 14:05,\"Zero, 6x9\",40.0,0.25,160,")
+        );
+        let md = to_markdown(&book.filter(""));
+        assert!(md.contains("| Pier \\| west end |"));
+        assert!(md.contains("| 2.5s |"));
+    }
+}
@@ -0,0 +1,184 @@
+// What a scanned pinhole negative should say about the camera that took it, as
+// EXIF values and an XMP sidecar that darktable, digiKam and Lightroom read.
+use std::fmt::Write;
+
+use crate::design::Design;
+use crate::formats;
+use crate::lensfun::FULL_FRAME_DIAGONAL;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Metadata {
+    pub focal_length: f32,
+    pub fnumber: f32,
+    pub focal_length_35mm: u16,
+    pub film: String,
+    pub exposure_time: Option<f32>, // s
+    pub lens_model: String,
+}
+
+impl Metadata {
+    // The design's own film and a lens name like lensfun::model.
+    pub fn new(design: &Design) -> Metadata {
+        let p = design.point();
+        Metadata {
+            focal_length: p.focal_length,
+            fnumber: p.fnumber(),
+            // Over the diagonal of the film the design names, or of the
+            // projection without a catalogue format.
+            focal_length_35mm: match design
+                .format
+                .as_deref()
+                .and_then(formats::find)
+                .or(p.format)
+            {
+                Some(f) => p.focal_length * crate::lensfun::crop_factor(f),
+                None => p.focal_length * FULL_FRAME_DIAGONAL / p.projection,
+            }
+            .round() as u16,
+            film: design.film.clone(),
+            exposure_time: None,
+            lens_model: crate::lensfun::model(design),
+        }
+    }
+
+    // The film stock, EXIF has no field for it.
+    pub fn comment(&self) -> Option<String> {
+        (!self.film.is_empty()).then(|| format!("Film: {}", self.film))
+    }
+
+    pub fn to_xmp(&self) -> String {
+        let mut x = String::new();
+        let _ = writeln!(
+            x,
+            "<?xpacket begin=\"\u{feff}\" id=\"W5M0MpCehiHzreSzNTczkc9d\"?>"
+        );
+        let _ = writeln!(x, "<x:xmpmeta xmlns:x=\"adobe:ns:meta/\">");
+        let _ = writeln!(
+            x,
+            " <rdf:RDF xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\">"
+        );
+        let _ = writeln!(x, "  <rdf:Description rdf:about=\"\"");
+        let _ = writeln!(x, "    xmlns:exif=\"http://ns.adobe.com/exif/1.0/\"");
+        let _ = writeln!(x, "    xmlns:exifEX=\"http://cipa.jp/exif/1.0/\"");
+        let _ = writeln!(x, "    xmlns:aux=\"http://ns.adobe.com/exif/1.0/aux/\"");
+        let [n, d] = rational(self.focal_length);
+        let _ = writeln!(x, "    exif:FocalLength=\"{n}/{d}\"");
+        let [n, d] = rational(self.fnumber);
+        let _ = writeln!(x, "    exif:FNumber=\"{n}/{d}\"");
+        let _ = writeln!(
+            x,
+            "    exif:FocalLengthIn35mmFilm=\"{}\"",
+            self.focal_length_35mm
+        );
+        if let Some(t) = self.exposure_time {
+            let [n, d] = exposure_rational(t);
+            let _ = writeln!(x, "    exif:ExposureTime=\"{n}/{d}\"");
+        }
+        let _ = writeln!(x, "    exifEX:LensModel=\"{}\"", escape(&self.lens_model));
+        let _ = write!(x, "    aux:Lens=\"{}\"", escape(&self.lens_model));
+        match self.comment() {
+            Some(comment) => {
+                let _ = writeln!(x, ">");
+                let _ = writeln!(x, "   <exif:UserComment>");
+                let _ = writeln!(x, "    <rdf:Alt>");
+                let _ = writeln!(
+                    x,
+                    "     <rdf:li xml:lang=\"x-default\">{}</rdf:li>",
+                    escape(&comment)
+                );
+                let _ = writeln!(x, "    </rdf:Alt>");
+                let _ = writeln!(x, "   </exif:UserComment>");
+                let _ = writeln!(x, "  </rdf:Description>");
+            }
+            None => {
+                let _ = writeln!(x, "/>");
+            }
+        }
+        let _ = writeln!(x, " </rdf:RDF>");
+        let _ = writeln!(x, "</x:xmpmeta>");
+        let _ = writeln!(x, "<?xpacket end=\"w\"?>");
+        x
+    }
+}
+
+// To a tenth, as cameras write focal lengths and f-numbers.
+pub fn rational(v: f32) -> [u32; 2] {
+    reduce((v * 10.).round() as u32, 10)
+}
+
+// Short times as 1/N like a shutter speed, long ones to a tenth of a second.
+pub fn exposure_rational(t: f32) -> [u32; 2] {
+    if t < 1. {
+        [1, (1. / t).round() as u32]
+    } else {
+        rational(t)
+    }
+}
+
+fn reduce(n: u32, d: u32) -> [u32; 2] {
+    let (mut a, mut b) = (n, d);
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    let g = a.max(1);
+    [n / g, d / g]
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rationals() {
+        assert_eq!(rational(25.), [25, 1]);
+        assert_eq!(rational(138.88), [1389, 10]);
+        assert_eq!(rational(0.5), [1, 2]);
+        assert_eq!(exposure_rational(1. / 125.), [1, 125]);
+        assert_eq!(exposure_rational(90.), [90, 1]);
+    }
+
+    #[test]
+    fn sidecar() {
+        let design = Design {
+            name: "Zero".to_string(),
+            focal_length: 25.,
+            diameter: 0.18,
+            projection: 43.267,
+            film: "Portra 400 & more".to_string(),
+            ..Default::default()
+        };
+        let mut m = Metadata::new(&design);
+        assert_eq!(m.focal_length_35mm, 25);
+        m.exposure_time = Some(4.);
+        let xmp = m.to_xmp();
+        assert!(xmp.contains("exif:FocalLength=\"25/1\""));
+        assert!(xmp.contains("exif:FNumber=\"1389/10\""));
+        assert!(xmp.contains("exif:ExposureTime=\"4/1\""));
+        assert!(xmp.contains("exifEX:LensModel=\"Zero 25mm f/139\""));
+        assert!(xmp.contains(">Film: Portra 400 &amp; more</rdf:li>"));
+    }
+
+    #[test]
+    fn equivalent_over_the_format() {
+        // 6x9 has a 108 mm diagonal, the projection is drawn wider.
+        let design = Design {
+            focal_length: 25.,
+            format: Some("6x9".to_string()),
+            projection: 150.,
+            ..Default::default()
+        };
+        assert_eq!(Metadata::new(&design).focal_length_35mm, 10);
+        let design = Design {
+            format: None,
+            ..design
+        };
+        assert_eq!(Metadata::new(&design).focal_length_35mm, 7);
+    }
+}
@@ -0,0 +1,73 @@
+// Light without a meter: the EV a kind of scene usually has, at ISO 100.
+// Rounded values from the usual exposure tables, good to a stop or so.
+use crate::exposure::{self, Exposure};
+use crate::film::Film;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Scene {
+    pub name: &'static str,
+    pub ev: f32,
+}
+
+const fn scene(name: &'static str, ev: f32) -> Scene {
+    Scene { name, ev }
+}
+
+pub const SCENES: [Scene; 14] = [
+    scene("Bright sun on snow or sand", 16.),
+    scene("Bright sun, hard shadows", 15.),
+    scene("Hazy sun, soft shadows", 14.),
+    scene("Cloudy bright, no shadows", 13.),
+    scene("Overcast", 12.),
+    scene("Open shade on a sunny day", 12.),
+    scene("Heavy overcast", 11.),
+    scene("Just after sunset", 9.),
+    scene("Bright interior, shop or office", 8.),
+    scene("Home interior by day", 6.),
+    scene("Street lights at night", 5.),
+    scene("Floodlit buildings", 3.),
+    scene("Landscape under a full moon", -3.),
+    scene("Landscape under a half moon", -5.),
+];
+
+impl Scene {
+    // The pinhole exposure for film of a speed, reciprocity when the film is known.
+    pub fn exposure(&self, iso: f32, n: f32, film: Option<&Film>) -> Exposure {
+        exposure::for_metered(exposure::metered_time(self.ev, iso, n), film)
+    }
+}
+
+// By name, ignoring case.
+pub fn find(name: &str) -> Option<&'static Scene> {
+    SCENES
+        .iter()
+        .find(|s| s.name.eq_ignore_ascii_case(name.trim()))
+}
+
+// The scene whose EV is closest, for a label next to a typed-in EV.
+pub fn nearest(ev: f32) -> &'static Scene {
+    SCENES
+        .iter()
+        .min_by(|a, b| (a.ev - ev).abs().total_cmp(&(b.ev - ev).abs()))
+        .expect("scenes")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sunny_sixteen_and_the_moon() {
+        let sun = find("bright sun, hard shadows").unwrap();
+        let e = sun.exposure(100., 16., None);
+        assert!((1. / e.metered - 128.).abs() < 1e-3);
+        assert_eq!(e.corrected, e.metered);
+        // Moonlight at f/180 takes most of a day, and more with reciprocity.
+        let hp5 = crate::film::find("HP5 Plus").unwrap();
+        let moon = find("Landscape under a full moon").unwrap();
+        let e = moon.exposure(hp5.iso, 180., Some(hp5));
+        assert!(e.metered > 600. && e.corrected > 3. * 3600.);
+        assert_eq!(nearest(11.8).name, "Overcast");
+        assert_eq!(nearest(-10.).ev, -5.);
+    }
+}
@@ -0,0 +1,80 @@
+// Blur spot on the film against the pinhole diameter. The shadow of the hole
+// grows with the diameter, the diffraction disc shrinks. With the Rayleigh
+// factor k the diffraction disc is k²·λ·f/d across, which puts the least total
+// blur at calc_optimalsize.
+use crate::sweep::Point;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Blur {
+    pub diameter: f32,    // of the pinhole, mm
+    pub geometric: f32,   // mm on the film
+    pub diffraction: f32, // mm on the film
+}
+
+impl Blur {
+    // The two added in quadrature.
+    pub fn total(&self) -> f32 {
+        self.geometric.hypot(self.diffraction)
+    }
+}
+
+// The blur of the design with another pinhole diameter.
+pub fn at_diameter(p: &Point, diameter: f32) -> Blur {
+    let wavelength = p.wavelength / 1000000.;
+    Blur {
+        diameter,
+        geometric: diameter * (1. + p.magnification()),
+        diffraction: p.rayleigh_factor.powi(2) * wavelength * p.focal_length / diameter,
+    }
+}
+
+pub fn blur(p: &Point) -> Blur {
+    at_diameter(p, p.diameter)
+}
+
+// Diameters from..to, in steps + 1 points.
+pub fn curve(p: &Point, from: f32, to: f32, steps: usize) -> Vec<Blur> {
+    (0..=steps)
+        .map(|i| at_diameter(p, from + (to - from) * i as f32 / steps as f32))
+        .collect()
+}
+
+// The diameters where the total blur is `within` (0.1 for 10%) worse than at
+// the optimum.
+pub fn tolerance(p: &Point, within: f32) -> (f32, f32) {
+    // Relative to the optimum x = d / d_opt the total is
+    // best · √((x² + 1/x²) / 2), solve for best · (1 + within).
+    let c = 2. * (1. + within).powi(2);
+    let x2 = (c + (c * c - 4.).sqrt()) / 2.;
+    let optimum = p.optimalsize();
+    (optimum / x2.sqrt(), optimum * x2.sqrt())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn least_blur_at_optimalsize() {
+        let p = Point {
+            subject_distance: 0.5,
+            ..Default::default()
+        };
+        let best = at_diameter(&p, p.optimalsize());
+        assert!((best.geometric - best.diffraction).abs() < 1e-6);
+        for d in [0.95, 1.05] {
+            assert!(at_diameter(&p, d * p.optimalsize()).total() > best.total());
+        }
+    }
+
+    #[test]
+    fn tolerance_is_ten_percent() {
+        let p = Point::default();
+        let best = at_diameter(&p, p.optimalsize()).total();
+        let (lo, hi) = tolerance(&p, 0.1);
+        assert!(lo < p.optimalsize() && p.optimalsize() < hi);
+        for d in [lo, hi] {
+            assert!((at_diameter(&p, d).total() / best - 1.1).abs() < 1e-4);
+        }
+    }
+}
@@ -0,0 +1,258 @@
+// Planning a solargraph: where the sun's daily arcs land on paper in a fixed
+// camera over weeks or months. Positions on the paper are mm from the centre,
+// x to the right and y up as the scene is seen, so a scan of the paper is the
+// right way round.
+use crate::formats::Format;
+use crate::sun::{self, Date, Place, Position};
+use crate::sweep::Point;
+
+// Which way the camera looks. Degrees, the facing clockwise from north and the
+// tilt of the axis above the horizon.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Mount {
+    pub facing: f64,
+    pub tilt: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Camera {
+    pub focal_length: f64,
+    // Half angles in degrees, as calc_viewangle.
+    pub viewangle: f64,
+    pub coverage_radius: f64,
+    // Half the paper, the long side across.
+    pub half_width: f64,
+    pub half_height: f64,
+}
+
+impl Camera {
+    pub fn new(p: &Point, format: &Format) -> Camera {
+        let [short, long, _] = format.radii();
+        Camera {
+            focal_length: p.focal_length as f64,
+            viewangle: p.viewangle() as f64,
+            coverage_radius: p.coverage_radius() as f64,
+            half_width: long as f64,
+            half_height: short as f64,
+        }
+    }
+
+    // Where the sun lands on the paper, if it gets through the pinhole and
+    // the paper is there.
+    pub fn project(&self, mount: &Mount, sun: &Position) -> Option<(f32, f32)> {
+        let (a, t) = (mount.facing.to_radians(), mount.tilt.to_radians());
+        let (e, z) = (sun.elevation.to_radians(), sun.azimuth.to_radians());
+        // East, north and up.
+        let s = [e.cos() * z.sin(), e.cos() * z.cos(), e.sin()];
+        let forward = [t.cos() * a.sin(), t.cos() * a.cos(), t.sin()];
+        let right = [a.cos(), -a.sin(), 0.];
+        let up = [-a.sin() * t.sin(), -a.cos() * t.sin(), t.cos()];
+        let dot = |v: [f64; 3]| v[0] * s[0] + v[1] * s[1] + v[2] * s[2];
+        let depth = dot(forward);
+        if depth <= 0. {
+            return None;
+        }
+        let x = self.focal_length * dot(right) / depth;
+        let y = self.focal_length * dot(up) / depth;
+        let on_paper = x.abs() <= self.half_width && y.abs() <= self.half_height;
+        (on_paper && x.hypot(y) <= self.coverage_radius).then_some((x as f32, y as f32))
+    }
+}
+
+// The sun on the paper for one step. Light is a fraction of full sun.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Mark {
+    pub x: f32,
+    pub y: f32,
+    pub light: f32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Day {
+    pub date: Date,
+    // Sun on the paper.
+    pub minutes: u32,
+    // The same in hours of full sun, what burns the trail.
+    pub hours: f32,
+    // Unbroken pieces of the trail.
+    pub arcs: Vec<Vec<Mark>>,
+    // Where the trail is when the sun is highest. On flat paper that need not
+    // be the top of the trail, summer arcs bend up towards the sides.
+    pub noon: Option<(f32, f32)>,
+}
+
+// One day of sun from solar midnight to solar midnight, every `step` minutes.
+// Only the sun above the horizon burns a trail.
+pub fn day(camera: &Camera, mount: &Mount, place: &Place, date: Date, step: u32) -> Day {
+    let step = step.max(1);
+    let midnight = -place.longitude / 15.;
+    let mut day = Day {
+        date,
+        minutes: 0,
+        hours: 0.,
+        arcs: Vec::new(),
+        noon: None,
+    };
+    let mut arc = Vec::new();
+    let mut highest = 0.;
+    for minute in (0..24 * 60).step_by(step as usize) {
+        let jd = date.julian_day(midnight + minute as f64 / 60.);
+        let sun = sun::position(place, jd);
+        let mark = camera
+            .project(mount, &sun)
+            .filter(|_| sun.elevation > 0.)
+            .zip(sun::daylight_ev(sun.elevation));
+        let Some(((x, y), ev)) = mark else {
+            if !arc.is_empty() {
+                day.arcs.push(std::mem::take(&mut arc));
+            }
+            continue;
+        };
+        let light = 2f32.powf(ev - 15.);
+        day.minutes += step;
+        day.hours += light * step as f32 / 60.;
+        if sun.elevation > highest {
+            highest = sun.elevation;
+            day.noon = Some((x, y));
+        }
+        arc.push(Mark { x, y, light });
+    }
+    if !arc.is_empty() {
+        day.arcs.push(arc);
+    }
+    day
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Plan {
+    pub days: Vec<Day>,
+    // The first June and December solstices from the start, the edges of
+    // the band of trails, named for the month. Only those before the end,
+    // a summer plan has neither.
+    pub solstices: Vec<(&'static str, Day)>,
+}
+
+impl Plan {
+    pub fn hours(&self) -> f32 {
+        self.days.iter().map(|d| d.hours).sum()
+    }
+}
+
+pub fn plan(
+    camera: &Camera,
+    mount: &Mount,
+    place: &Place,
+    from: Date,
+    to: Date,
+    step: u32,
+) -> Plan {
+    let days = (0..=from.days_until(&to).max(0))
+        .map(|i| day(camera, mount, place, from.add_days(i), step))
+        .collect();
+    let solstices = [("June", 6), ("December", 12)]
+        .into_iter()
+        .filter_map(|(name, month)| {
+            let mut date = Date::new(from.year, month, 21);
+            if date < from {
+                date.year += 1;
+            }
+            (date <= to).then(|| (name, day(camera, mount, place, date, step)))
+        })
+        .collect();
+    Plan { days, solstices }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GREENWICH: Place = Place {
+        latitude: 51.4769,
+        longitude: 0.,
+    };
+
+    fn camera() -> Camera {
+        let p = Point {
+            focal_length: 25.,
+            diameter: 0.3,
+            thickness: 0.1,
+            ..Default::default()
+        };
+        Camera::new(&p, crate::formats::find("6x9").unwrap())
+    }
+
+    #[test]
+    fn noon_straight_ahead() {
+        let south = Mount {
+            facing: 180.,
+            tilt: 30.,
+        };
+        let sun = Position {
+            elevation: 38.5,
+            azimuth: 180.,
+        };
+        let (x, y) = camera().project(&south, &sun).unwrap();
+        assert!(x.abs() < 1e-4 && (y - 25. * 8.5f32.to_radians().tan()).abs() < 1e-3);
+        // East is on the left looking south, and behind the camera is nothing.
+        let east = Position {
+            elevation: 30.,
+            azimuth: 170.,
+        };
+        assert!(camera().project(&south, &east).unwrap().0 < 0.);
+        let north = Mount {
+            facing: 0.,
+            tilt: 30.,
+        };
+        assert_eq!(camera().project(&north, &sun), None);
+    }
+
+    #[test]
+    fn half_a_year_between_the_solstices() {
+        let south = Mount {
+            facing: 180.,
+            tilt: 30.,
+        };
+        let plan = plan(
+            &camera(),
+            &south,
+            &GREENWICH,
+            Date::new(2025, 6, 21),
+            Date::new(2025, 12, 21),
+            5,
+        );
+        assert_eq!(plan.days.len(), 184);
+        let [(_, june), (_, december)] = &plan.solstices[..] else {
+            panic!("two solstices");
+        };
+        assert_eq!(june.date, Date::new(2025, 6, 21));
+        assert_eq!(december.date, Date::new(2025, 12, 21));
+        // Summer trails run above the winter ones, and get more sun.
+        let (_, summer) = june.noon.unwrap();
+        let (_, winter) = december.noon.unwrap();
+        assert!(summer > winter + 20., "{summer} {winter}");
+        assert!(june.hours > december.hours);
+        assert!(plan.days.iter().all(|d| d.minutes > 0));
+        // Facing north no sun lands in summer or winter.
+        let north = Mount {
+            facing: 0.,
+            tilt: 0.,
+        };
+        let day = day(&camera(), &north, &GREENWICH, Date::new(2025, 12, 21), 5);
+        assert_eq!(day.minutes, 0);
+    }
+
+    #[test]
+    fn only_the_solstices_in_the_plan() {
+        let south = Mount {
+            facing: 180.,
+            tilt: 30.,
+        };
+        let between = |from, to| plan(&camera(), &south, &GREENWICH, from, to, 60);
+        let summer = between(Date::new(2025, 7, 1), Date::new(2025, 9, 1));
+        assert_eq!(summer.days.len(), 63);
+        assert!(summer.solstices.is_empty());
+        let winter = between(Date::new(2025, 9, 1), Date::new(2026, 1, 31));
+        let names: Vec<_> = winter.solstices.iter().map(|(name, _)| *name).collect();
+        assert_eq!(names, ["December"]);
+    }
+}
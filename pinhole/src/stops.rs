@@ -0,0 +1,265 @@
+// Stops as marked on lenses and meters, and exact stop arithmetic. Every stop
+// halves the light: a time or ISO doubles per stop, an f-number goes up √2.
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Division {
+    Full,
+    Half,
+    Third,
+}
+
+impl Division {
+    pub fn steps(self) -> u32 {
+        match self {
+            Division::Full => 1,
+            Division::Half => 2,
+            Division::Third => 3,
+        }
+    }
+}
+
+// The customary marks from f/1 to f/64. Further up every mark is the one two
+// stops down doubled, as pinhole charts have f/180, f/360 and f/720.
+const THIRDS: [f32; 36] = [
+    1., 1.1, 1.2, 1.4, 1.6, 1.8, 2., 2.2, 2.5, 2.8, 3.2, 3.5, 4., 4.5, 5., 5.6, 6.3, 7.1, 8., 9.,
+    10., 11., 13., 14., 16., 18., 20., 22., 25., 28., 32., 36., 40., 45., 51., 57.,
+];
+const HALVES: [f32; 24] = [
+    1., 1.2, 1.4, 1.7, 2., 2.4, 2.8, 3.3, 4., 4.8, 5.6, 6.7, 8., 9.5, 11., 13., 16., 19., 22., 27.,
+    32., 38., 45., 54.,
+];
+
+// A marked stop, `index` steps of the division above f/1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Stop {
+    pub division: Division,
+    pub index: u32,
+}
+
+impl Stop {
+    // Stops above f/1.
+    pub fn stops(&self) -> f32 {
+        self.index as f32 / self.division.steps() as f32
+    }
+
+    pub fn exact(&self) -> f32 {
+        fnumber(1., self.stops())
+    }
+
+    // The f-number written on the dial.
+    pub fn marked(&self) -> f32 {
+        let (table, per_doubling): (&[f32], u32) = match self.division {
+            Division::Full => (&THIRDS, 6),
+            Division::Half => (&HALVES, 4),
+            Division::Third => (&THIRDS, 6),
+        };
+        // A full stop is every third third.
+        let i = match self.division {
+            Division::Full => 3 * self.index,
+            _ => self.index,
+        };
+        let len = table.len() as u32;
+        if i < len {
+            return table[i as usize];
+        }
+        let above = i - (len - per_doubling);
+        let base = table[(len - per_doubling + above % per_doubling) as usize];
+        base * 2f32.powi((above / per_doubling) as i32)
+    }
+}
+
+impl fmt::Display for Stop {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let n = self.marked();
+        if n.fract() == 0. {
+            write!(f, "f/{n:.0}")
+        } else {
+            write!(f, "f/{n:.1}")
+        }
+    }
+}
+
+// The marked stops from f/1 up to f-number `max`.
+pub fn series(division: Division, max: f32) -> Vec<Stop> {
+    (0..)
+        .map(|index| Stop { division, index })
+        .take_while(|s| s.exact() <= max * 1.01)
+        .collect()
+}
+
+// The marked stop closest to an f-number, f/1 at the least.
+pub fn nearest(n: f32, division: Division) -> Stop {
+    let steps = division.steps() as f32;
+    Stop {
+        division,
+        index: (between_fnumbers(1., n) * steps).round().max(0.) as u32,
+    }
+}
+
+// An f-number as a full stop and the part of a stop above it, "f/256 + ⅔".
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Offset {
+    pub stop: Stop,
+    pub steps: u32,
+    pub division: Division,
+}
+
+pub fn offset(n: f32, division: Division) -> Offset {
+    let index = nearest(n, division).index;
+    let per_stop = division.steps();
+    Offset {
+        stop: Stop {
+            division: Division::Full,
+            index: index / per_stop,
+        },
+        steps: index % per_stop,
+        division,
+    }
+}
+
+impl fmt::Display for Offset {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.stop)?;
+        match fraction(self.steps, self.division) {
+            "" => Ok(()),
+            part => write!(f, " + {part}"),
+        }
+    }
+}
+
+fn fraction(steps: u32, division: Division) -> &'static str {
+    match (steps, division) {
+        (1, Division::Half) => "½",
+        (1, Division::Third) => "⅓",
+        (2, Division::Third) => "⅔",
+        _ => "",
+    }
+}
+
+// A number of stops to the nearest third, "4⅓" or "-⅔".
+pub fn thirds(stops: f32) -> String {
+    let t = (stops * 3.).round() as i32;
+    let sign = if t < 0 { "-" } else { "" };
+    let (whole, part) = (t.abs() / 3, fraction((t.abs() % 3) as u32, Division::Third));
+    match (whole, part) {
+        (0, "") => "0".to_string(),
+        (0, part) => format!("{sign}{part}"),
+        (whole, part) => format!("{sign}{whole}{part}"),
+    }
+}
+
+// Stops from one value to the other, positive when it takes more exposure.
+pub fn between_fnumbers(n0: f32, n1: f32) -> f32 {
+    2. * (n1 / n0).log2()
+}
+
+pub fn between_times(t0: f32, t1: f32) -> f32 {
+    (t1 / t0).log2()
+}
+
+// Positive when the second film is faster, and needs less exposure.
+pub fn between_isos(iso0: f32, iso1: f32) -> f32 {
+    (iso1 / iso0).log2()
+}
+
+// A value this many stops further, more light needed for a positive count.
+pub fn fnumber(n: f32, stops: f32) -> f32 {
+    n * 2f32.powf(stops / 2.)
+}
+
+pub fn time(t: f32, stops: f32) -> f32 {
+    t * 2f32.powf(stops)
+}
+
+// A film this many stops slower.
+pub fn iso(iso: f32, stops: f32) -> f32 {
+    iso * 2f32.powf(-stops)
+}
+
+// The time at f-number `to` that gives the light of `t` at `from`.
+pub fn time_at(t: f32, from: f32, to: f32) -> f32 {
+    time(t, between_fnumbers(from, to))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn marks(division: Division, max: f32) -> Vec<String> {
+        series(division, max)
+            .iter()
+            .map(|s| s.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn full_stops_to_f1024() {
+        let full = marks(Division::Full, 1024.);
+        assert_eq!(full.len(), 21);
+        assert_eq!(
+            full[9..],
+            [
+                "f/22", "f/32", "f/45", "f/64", "f/90", "f/128", "f/180", "f/256", "f/360",
+                "f/512", "f/720", "f/1024"
+            ]
+        );
+        assert_eq!(marks(Division::Full, 2048.)[22], "f/2048");
+        assert_eq!(
+            marks(Division::Full, 4.),
+            ["f/1", "f/1.4", "f/2", "f/2.8", "f/4"]
+        );
+    }
+
+    #[test]
+    fn half_and_third_stops() {
+        assert_eq!(
+            marks(Division::Half, 256.)[20..],
+            [
+                "f/32", "f/38", "f/45", "f/54", "f/64", "f/76", "f/90", "f/108", "f/128", "f/152",
+                "f/180", "f/216", "f/256"
+            ]
+        );
+        assert_eq!(
+            marks(Division::Third, 256.)[42..],
+            [
+                "f/128", "f/144", "f/160", "f/180", "f/204", "f/228", "f/256"
+            ]
+        );
+        // The marks stay within a few percent of the exact values.
+        for s in series(Division::Third, 4096.) {
+            assert!((s.marked() / s.exact() - 1.).abs() < 0.06, "{s}");
+        }
+    }
+
+    #[test]
+    fn nearest_marks() {
+        assert_eq!(nearest(138.9, Division::Full).to_string(), "f/128");
+        assert_eq!(nearest(138.9, Division::Third).to_string(), "f/144");
+        assert_eq!(offset(138.9, Division::Third).to_string(), "f/128 + ⅓");
+        assert_eq!(offset(320., Division::Third).to_string(), "f/256 + ⅔");
+        assert_eq!(offset(190., Division::Half).to_string(), "f/180");
+        assert_eq!(offset(0.5, Division::Third).to_string(), "f/1");
+    }
+
+    #[test]
+    fn arithmetic() {
+        assert!((between_fnumbers(32., 64.) - 2.).abs() < 1e-6);
+        assert!((fnumber(128., 1.) - 181.02).abs() < 0.01);
+        assert_eq!(time(2., 3.), 16.);
+        assert_eq!(iso(400., 2.), 100.);
+        assert_eq!(between_isos(100., 400.), 2.);
+        assert_eq!(between_times(1., 8.), 3.);
+        // Same as exposure_factor.
+        let t = time_at(1. / 125., 32., 138.9);
+        assert!((t / (crate::exposure_factor(32., 138.9) / 125.) - 1.).abs() < 1e-5);
+    }
+
+    #[test]
+    fn thirds_of_stops() {
+        assert_eq!(thirds(4.236), "4⅓");
+        assert_eq!(thirds(-0.7), "-⅔");
+        assert_eq!(thirds(0.1), "0");
+        assert_eq!(thirds(2.), "2");
+    }
+}
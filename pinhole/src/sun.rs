@@ -0,0 +1,315 @@
+// Where the sun is, and how bright a clear day is for it. The position is
+// NOAA's solar calculator (after Meeus), good to a minute of arc for dates
+// around now. Times are hours UTC unless a function takes an offset.
+use std::fmt;
+use std::str::FromStr;
+
+use crate::design::Design;
+use crate::exposure::{self, Exposure};
+use crate::film::Film;
+
+// Degrees, north and east positive.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Place {
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Date {
+    pub year: i32,
+    pub month: u32,
+    pub day: u32,
+}
+
+impl Date {
+    pub fn new(year: i32, month: u32, day: u32) -> Date {
+        Date { year, month, day }
+    }
+
+    // Julian day at a time of day, UTC.
+    pub fn julian_day(&self, hours: f64) -> f64 {
+        let (mut y, mut m) = (self.year as f64, self.month as f64);
+        if m <= 2. {
+            y -= 1.;
+            m += 12.;
+        }
+        let a = (y / 100.).floor();
+        let b = 2. - a + (a / 4.).floor();
+        (365.25 * (y + 4716.)).floor() + (30.6001 * (m + 1.)).floor() + self.day as f64 + b - 1524.5
+            + hours / 24.
+    }
+
+    // The date a Julian day falls on, UTC.
+    pub fn from_julian_day(jd: f64) -> Date {
+        let z = (jd + 0.5).floor();
+        let alpha = ((z - 1867216.25) / 36524.25).floor();
+        let a = z + 1. + alpha - (alpha / 4.).floor();
+        let b = a + 1524.;
+        let c = ((b - 122.1) / 365.25).floor();
+        let d = (365.25 * c).floor();
+        let e = ((b - d) / 30.6001).floor();
+        let day = b - d - (30.6001 * e).floor();
+        let month = if e < 14. { e - 1. } else { e - 13. };
+        let year = if month > 2. { c - 4716. } else { c - 4715. };
+        Date::new(year as i32, month as u32, day as u32)
+    }
+
+    pub fn add_days(&self, days: i64) -> Date {
+        Date::from_julian_day(self.julian_day(12.) + days as f64)
+    }
+
+    // Days from self to other.
+    pub fn days_until(&self, other: &Date) -> i64 {
+        (other.julian_day(12.) - self.julian_day(12.)).round() as i64
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+// "2025-06-21".
+impl FromStr for Date {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Date, String> {
+        let invalid = || format!("'{s}' is not a date like 2025-06-21");
+        let mut parts = s.trim().splitn(3, '-');
+        let mut next = || parts.next().and_then(|p| p.parse::<u32>().ok());
+        let (Some(year), Some(month), Some(day)) = (next(), next(), next()) else {
+            return Err(invalid());
+        };
+        let date = Date::new(year as i32, month, day);
+        // Out of range days come back as another date.
+        if (1..=12).contains(&month) && Date::from_julian_day(date.julian_day(12.)) == date {
+            Ok(date)
+        } else {
+            Err(invalid())
+        }
+    }
+}
+
+// Degrees. Elevation above the horizon with refraction, azimuth clockwise
+// from north.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Position {
+    pub elevation: f64,
+    pub azimuth: f64,
+}
+
+pub fn position(place: &Place, jd: f64) -> Position {
+    let t = (jd - 2451545.) / 36525.;
+    let l0 = (280.46646 + t * (36000.76983 + t * 0.0003032)).rem_euclid(360.);
+    let m = 357.52911 + t * (35999.05029 - 0.0001537 * t);
+    let e = 0.016708634 - t * (0.000042037 + 0.0000001267 * t);
+    let mr = m.to_radians();
+    let centre = mr.sin() * (1.914602 - t * (0.004817 + 0.000014 * t))
+        + (2. * mr).sin() * (0.019993 - 0.000101 * t)
+        + (3. * mr).sin() * 0.000289;
+    let omega = (125.04 - 1934.136 * t).to_radians();
+    let lambda = (l0 + centre - 0.00569 - 0.00478 * omega.sin()).to_radians();
+    let eps0 = 23. + (26. + (21.448 - t * (46.815 + t * (0.00059 - t * 0.001813))) / 60.) / 60.;
+    let eps = (eps0 + 0.00256 * omega.cos()).to_radians();
+    let declination = (eps.sin() * lambda.sin()).asin();
+
+    // Equation of time in minutes.
+    let y = (eps / 2.).tan().powi(2);
+    let l0r = l0.to_radians();
+    let equation = 4.
+        * (y * (2. * l0r).sin() - 2. * e * mr.sin() + 4. * e * y * mr.sin() * (2. * l0r).cos()
+            - 0.5 * y * y * (4. * l0r).sin()
+            - 1.25 * e * e * (2. * mr).sin())
+        .to_degrees();
+    let minutes = (jd + 0.5).rem_euclid(1.) * 1440.;
+    let solar = (minutes + equation + 4. * place.longitude).rem_euclid(1440.);
+    let hour_angle = (solar / 4. - 180.).to_radians();
+
+    let lat = place.latitude.to_radians();
+    let elevation = (lat.sin() * declination.sin()
+        + lat.cos() * declination.cos() * hour_angle.cos())
+    .clamp(-1., 1.)
+    .asin()
+    .to_degrees();
+    let azimuth = (180.
+        + hour_angle
+            .sin()
+            .atan2(hour_angle.cos() * lat.sin() - declination.tan() * lat.cos())
+            .to_degrees())
+    .rem_euclid(360.);
+    Position {
+        elevation: elevation + refraction(elevation),
+        azimuth,
+    }
+}
+
+// NOAA's approximation, degrees to add to the true elevation.
+fn refraction(elevation: f64) -> f64 {
+    let tan = elevation.to_radians().tan();
+    let seconds = if elevation > 85. {
+        0.
+    } else if elevation > 5. {
+        58.1 / tan - 0.07 / tan.powi(3) + 0.000086 / tan.powi(5)
+    } else if elevation > -0.575 {
+        1735.
+            + elevation * (-518.2 + elevation * (103.4 + elevation * (-12.79 + elevation * 0.711)))
+    } else {
+        -20.772 / tan
+    };
+    seconds / 3600.
+}
+
+// Sun elevation and the EV at ISO 100 of a frontlit scene under a clear sky,
+// from the exposure tables: sunny 16 once the sun is well up, a stop less low
+// in the sky, and down through twilight to the end of nautical dusk.
+const DAYLIGHT: [(f64, f32); 10] = [
+    (-12., -2.),
+    (-6., 4.),
+    (-4., 6.),
+    (0., 9.),
+    (2., 11.),
+    (5., 12.),
+    (10., 13.),
+    (20., 14.),
+    (35., 14.7),
+    (50., 15.),
+];
+
+// None at night, when the sun is more than 12° down.
+pub fn daylight_ev(elevation: f64) -> Option<f32> {
+    let (first, _) = DAYLIGHT[0];
+    if elevation < first {
+        return None;
+    }
+    let i = DAYLIGHT.iter().position(|(e, _)| *e >= elevation);
+    let Some(i) = i.filter(|i| *i > 0) else {
+        return Some(DAYLIGHT[i.unwrap_or(DAYLIGHT.len() - 1)].1);
+    };
+    let ((e0, ev0), (e1, ev1)) = (DAYLIGHT[i - 1], DAYLIGHT[i]);
+    Some(ev0 + (ev1 - ev0) * ((elevation - e0) / (e1 - e0)) as f32)
+}
+
+// The clear-sky light from a moment on, for exposure::through: the EV at a
+// time in s after `hours` UTC on the date.
+pub fn light_from(place: Place, date: Date, hours: f64) -> impl Fn(f32) -> Option<f32> {
+    move |t| {
+        let jd = date.julian_day(hours + t as f64 / 3600.);
+        daylight_ev(position(&place, jd).elevation)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sample {
+    // Local time of day in hours.
+    pub hours: f64,
+    pub sun: Position,
+    pub ev: Option<f32>,
+    pub exposure: Option<Exposure>,
+}
+
+// The exposure through a day for a design and film, every `step` minutes of
+// local time. `utc_offset` is the local time zone in hours, +1 for CET.
+pub fn curve(
+    place: &Place,
+    date: &Date,
+    utc_offset: f64,
+    design: &Design,
+    film: &Film,
+    step: u32,
+) -> Vec<Sample> {
+    let n = design.point().fnumber();
+    (0..24 * 60)
+        .step_by(step.max(1) as usize)
+        .map(|minute| {
+            let hours = minute as f64 / 60.;
+            let sun = position(place, date.julian_day(hours - utc_offset));
+            let ev = daylight_ev(sun.elevation);
+            Sample {
+                hours,
+                sun,
+                ev,
+                exposure: ev.map(|ev| exposure::for_ev(ev, n, film)),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GREENWICH: Place = Place {
+        latitude: 51.4769,
+        longitude: 0.,
+    };
+
+    #[test]
+    fn julian_days() {
+        assert_eq!(Date::new(2000, 1, 1).julian_day(12.), 2451545.);
+        assert_eq!(Date::new(1987, 6, 19).julian_day(12.), 2446966.);
+        let d = Date::new(2024, 2, 28);
+        assert_eq!(d.add_days(1), Date::new(2024, 2, 29));
+        assert_eq!(d.add_days(2), Date::new(2024, 3, 1));
+        assert_eq!(d.add_days(-59), Date::new(2023, 12, 31));
+        assert_eq!(d.days_until(&Date::new(2025, 2, 28)), 366);
+        assert_eq!("2024-02-29".parse(), Ok(Date::new(2024, 2, 29)));
+        assert_eq!(Date::new(2025, 6, 1).to_string(), "2025-06-01");
+        assert!("2025-02-29".parse::<Date>().is_err());
+        assert!("2025-6".parse::<Date>().is_err());
+    }
+
+    #[test]
+    fn midsummer_noon_at_greenwich() {
+        // Solar noon is at 12:01:50 UTC, the sun 90 - 51.48 + 23.44 up.
+        let noon = position(
+            &GREENWICH,
+            Date::new(2024, 6, 20).julian_day(12. + 1.8 / 60.),
+        );
+        assert!((noon.elevation - 61.97).abs() < 0.05, "{noon:?}");
+        assert!((noon.azimuth - 180.).abs() < 0.5, "{noon:?}");
+        // Sunrise there is at 03:43 UTC, north east, the top of the sun on the
+        // horizon and its centre a quarter degree down.
+        let rise = position(
+            &GREENWICH,
+            Date::new(2024, 6, 20).julian_day(3. + 43. / 60.),
+        );
+        assert!((rise.elevation + 0.27).abs() < 0.15, "{rise:?}");
+        assert!((rise.azimuth - 49.).abs() < 1., "{rise:?}");
+        // Midwinter noon is low in the south.
+        let winter = position(&GREENWICH, Date::new(2024, 12, 21).julian_day(11.97));
+        assert!((winter.elevation - 15.1).abs() < 0.1, "{winter:?}");
+    }
+
+    #[test]
+    fn daylight_through_the_day() {
+        assert_eq!(daylight_ev(-20.), None);
+        assert_eq!(daylight_ev(-12.), Some(-2.));
+        assert_eq!(daylight_ev(60.), Some(15.));
+        assert!((daylight_ev(1.).unwrap() - 10.).abs() < 1e-6);
+        let hp5 = crate::film::find("HP5 Plus").unwrap();
+        let design = Design::default();
+        let day = curve(&GREENWICH, &Date::new(2024, 6, 20), 1., &design, hp5, 30);
+        assert_eq!(day.len(), 48);
+        // Night around 1 am summer time, sunny 16 at 1 pm and never shorter.
+        assert!(day[2].exposure.is_none());
+        assert_eq!(day[26].ev, Some(15.));
+        let shortest = day[26].exposure.unwrap().corrected;
+        assert!(
+            day.iter()
+                .flat_map(|s| s.exposure)
+                .all(|e| e.corrected >= shortest)
+        );
+    }
+
+    #[test]
+    fn dusk_runs_long() {
+        // Half an hour after a midsummer sunset the light falls fast.
+        let hp5 = crate::film::find("HP5 Plus").unwrap();
+        let date = Date::new(2024, 6, 20);
+        let light = light_from(GREENWICH, date, 20.75);
+        let dusk = exposure::through(light, hp5.iso, 180., Some(hp5), 4. * 3600.).unwrap();
+        assert!(dusk.duration > 1.3 * dusk.at_start.corrected, "{dusk:?}");
+    }
+}
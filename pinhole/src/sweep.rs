@@ -0,0 +1,562 @@
+// Parameter sweeps: every combination of a set of input values, one row each,
+// written out as CSV, TSV or JSON.
+use std::io::{self, Write};
+
+use crate::formats::{FORMATS, Format};
+
+// One camera, the inputs of every calculation. Lengths in mm, wavelength in nm
+// and subject distance in m, as in the GUI.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Point {
+    pub diameter: f32,
+    pub thickness: f32,
+    pub focal_length: f32,
+    pub projection: f32,
+    pub format: Option<&'static Format>,
+    pub wavelength: f32,
+    pub rayleigh_factor: f32,
+    pub subject_distance: f32,
+}
+
+impl Default for Point {
+    fn default() -> Self {
+        Self {
+            diameter: 0.3,
+            thickness: 0.04,
+            focal_length: 50.,
+            projection: 44.,
+            format: None,
+            wavelength: 550.,
+            rayleigh_factor: 1.56,
+            subject_distance: f32::INFINITY,
+        }
+    }
+}
+
+impl Point {
+    // See closeup, the focal length is the image distance.
+    pub fn magnification(&self) -> f32 {
+        crate::closeup::magnification(self.subject_distance * 1000., self.focal_length)
+    }
+
+    pub fn fnumber(&self) -> f32 {
+        crate::fnumber(self.focal_length, self.diameter)
+    }
+
+    // Half the total view angle, like calc_viewangle.
+    pub fn viewangle(&self) -> f32 {
+        crate::calc_viewangle(self.diameter, self.thickness)
+    }
+
+    pub fn optimalsize(&self) -> f32 {
+        crate::calc_optimalsize(
+            self.focal_length,
+            self.wavelength,
+            self.rayleigh_factor,
+            self.magnification(),
+        )
+    }
+
+    pub fn vignetting(&self) -> (f32, f32) {
+        crate::calc_vignetting(self.focal_length, self.projection / 2.)
+    }
+
+    pub fn coverage_radius(&self) -> f32 {
+        crate::coverage_radius(self.focal_length, self.viewangle())
+    }
+
+    // Focal length at which the view angle just covers the projection.
+    pub fn needed_focallength(&self) -> f32 {
+        crate::needed_focallength(self.projection / 2., self.viewangle())
+    }
+
+    fn set(&mut self, param: Param, value: f32) {
+        match param {
+            Param::Diameter => self.diameter = value,
+            Param::Thickness => self.thickness = value,
+            Param::FocalLength => self.focal_length = value,
+            Param::Projection => {
+                self.projection = value;
+                self.format = None;
+            }
+            Param::Wavelength => self.wavelength = value,
+            Param::RayleighFactor => self.rayleigh_factor = value,
+            Param::SubjectDistance => self.subject_distance = value,
+        }
+    }
+
+    fn set_format(&mut self, format: &'static Format) {
+        self.projection = format.projection_diameter();
+        self.format = Some(format);
+    }
+}
+
+// The numeric inputs that can be swept.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Param {
+    Diameter,
+    Thickness,
+    FocalLength,
+    Projection,
+    Wavelength,
+    RayleighFactor,
+    SubjectDistance,
+}
+
+impl Param {
+    // The column showing this input.
+    pub fn column(&self) -> Column {
+        match self {
+            Param::Diameter => Column::Diameter,
+            Param::Thickness => Column::Thickness,
+            Param::FocalLength => Column::FocalLength,
+            Param::Projection => Column::Projection,
+            Param::Wavelength => Column::Wavelength,
+            Param::RayleighFactor => Column::RayleighFactor,
+            Param::SubjectDistance => Column::SubjectDistance,
+        }
+    }
+}
+
+// Values for one input, either listed or as an inclusive range.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Values {
+    List(Vec<f32>),
+    Range { start: f32, end: f32, step: f32 },
+}
+
+// More rows than any table is worth, likely a step in the wrong unit.
+const MAX_VALUES: f32 = 1e6;
+// The same for all the axes together.
+const MAX_POINTS: usize = 10_000_000;
+
+impl Values {
+    // A range has to run upwards in steps above zero.
+    pub fn to_vec(&self) -> Result<Vec<f32>, String> {
+        match *self {
+            Values::List(ref v) => Ok(v.clone()),
+            Values::Range { start, end, step } => {
+                if !(step.is_finite() && step > 0.) {
+                    return Err(format!(
+                        "the step of a range must be above zero, not {step}"
+                    ));
+                }
+                if !(start.is_finite() && end.is_finite()) {
+                    return Err(format!("range {start}..{end} has no end"));
+                }
+                if end < start {
+                    return Err(format!("range {start}..{end} runs downwards"));
+                }
+                // Multiply instead of adding up steps, and allow for rounding at the end.
+                let n = ((end - start) / step + 1e-4).floor();
+                if n >= MAX_VALUES {
+                    return Err(format!(
+                        "range {start}..{end} in steps of {step} is too long"
+                    ));
+                }
+                Ok((0..n as usize + 1)
+                    .map(|i| start + i as f32 * step)
+                    .collect())
+            }
+        }
+    }
+}
+
+enum Axis {
+    Param(Param, Vec<f32>),
+    Format(Vec<&'static Format>),
+}
+
+impl Axis {
+    fn len(&self) -> usize {
+        match self {
+            Axis::Param(_, v) => v.len(),
+            Axis::Format(v) => v.len(),
+        }
+    }
+
+    fn apply(&self, point: &mut Point, i: usize) {
+        match self {
+            Axis::Param(param, v) => point.set(*param, v[i]),
+            Axis::Format(v) => point.set_format(v[i]),
+        }
+    }
+}
+
+// The Cartesian product of the swept inputs around a base point. The last
+// axis added changes fastest.
+pub struct Sweep {
+    base: Point,
+    axes: Vec<Axis>,
+}
+
+impl Sweep {
+    pub fn new(base: Point) -> Self {
+        Self {
+            base,
+            axes: Vec::new(),
+        }
+    }
+
+    pub fn param(mut self, param: Param, values: &Values) -> Result<Self, String> {
+        self.axes.push(Axis::Param(param, values.to_vec()?));
+        match self.count() {
+            Some(n) if n <= MAX_POINTS => Ok(self),
+            _ => Err(format!(
+                "the sweep has more than {MAX_POINTS} points, give fewer values"
+            )),
+        }
+    }
+
+    pub fn formats(mut self, formats: Vec<&'static Format>) -> Self {
+        self.axes.push(Axis::Format(formats));
+        self
+    }
+
+    pub fn all_formats(self) -> Self {
+        self.formats(FORMATS.iter().collect())
+    }
+
+    // None when it does not fit a usize.
+    fn count(&self) -> Option<usize> {
+        self.axes
+            .iter()
+            .try_fold(1usize, |n, axis| n.checked_mul(axis.len()))
+    }
+
+    // Only `param` checks the size, a sweep too large to count has
+    // usize::MAX points.
+    pub fn len(&self) -> usize {
+        self.count().unwrap_or(usize::MAX)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn iter(&self) -> Points<'_> {
+        Points {
+            sweep: self,
+            index: vec![0; self.axes.len()],
+            done: self.is_empty(),
+        }
+    }
+}
+
+// Iterator over the points of a sweep, counting through the axes like an odometer.
+pub struct Points<'a> {
+    sweep: &'a Sweep,
+    index: Vec<usize>,
+    done: bool,
+}
+
+impl Iterator for Points<'_> {
+    type Item = Point;
+
+    fn next(&mut self) -> Option<Point> {
+        if self.done {
+            return None;
+        }
+        let mut point = self.sweep.base;
+        for (axis, &i) in self.sweep.axes.iter().zip(&self.index) {
+            axis.apply(&mut point, i);
+        }
+        self.done = true;
+        for (axis, i) in self.sweep.axes.iter().zip(self.index.iter_mut()).rev() {
+            *i += 1;
+            if *i < axis.len() {
+                self.done = false;
+                break;
+            }
+            *i = 0;
+        }
+        Some(point)
+    }
+}
+
+// Everything a row can show, inputs and results.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Column {
+    Format,
+    Diameter,
+    Thickness,
+    FocalLength,
+    Projection,
+    Wavelength,
+    RayleighFactor,
+    SubjectDistance,
+    Magnification,
+    FNumber,
+    StopsFromF32,
+    ExposureFactor,
+    ViewAngle,
+    CoverageDiameter,
+    NeededFocalLength,
+    OptimalDiameter,
+    Vignetting,
+    VignettingStops,
+    VignettingAngle,
+}
+
+pub enum Value {
+    Number(f32),
+    Text(&'static str),
+}
+
+impl Column {
+    pub const ALL: [Column; 19] = [
+        Column::Format,
+        Column::Diameter,
+        Column::Thickness,
+        Column::FocalLength,
+        Column::Projection,
+        Column::Wavelength,
+        Column::RayleighFactor,
+        Column::SubjectDistance,
+        Column::Magnification,
+        Column::FNumber,
+        Column::StopsFromF32,
+        Column::ExposureFactor,
+        Column::ViewAngle,
+        Column::CoverageDiameter,
+        Column::NeededFocalLength,
+        Column::OptimalDiameter,
+        Column::Vignetting,
+        Column::VignettingStops,
+        Column::VignettingAngle,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Column::Format => "format",
+            Column::Diameter => "diameter",
+            Column::Thickness => "thickness",
+            Column::FocalLength => "focal_length",
+            Column::Projection => "projection",
+            Column::Wavelength => "wavelength",
+            Column::RayleighFactor => "rayleigh_factor",
+            Column::SubjectDistance => "subject_distance",
+            Column::Magnification => "magnification",
+            Column::FNumber => "fnumber",
+            Column::StopsFromF32 => "from_f32",
+            Column::ExposureFactor => "exposure_factor",
+            Column::ViewAngle => "view_angle",
+            Column::CoverageDiameter => "coverage_diameter",
+            Column::NeededFocalLength => "needed_focal_length",
+            Column::OptimalDiameter => "optimal_diameter",
+            Column::Vignetting => "illumination",
+            Column::VignettingStops => "vignetting",
+            Column::VignettingAngle => "vignetting_angle",
+        }
+    }
+
+    pub fn unit(&self) -> &'static str {
+        match self {
+            Column::Diameter
+            | Column::Thickness
+            | Column::FocalLength
+            | Column::Projection
+            | Column::CoverageDiameter
+            | Column::NeededFocalLength
+            | Column::OptimalDiameter => "mm",
+            Column::Wavelength => "nm",
+            Column::SubjectDistance => "m",
+            Column::ViewAngle | Column::VignettingAngle => "deg",
+            Column::StopsFromF32 | Column::VignettingStops => "stops",
+            _ => "",
+        }
+    }
+
+    // Name with the unit, for a header line.
+    pub fn label(&self) -> String {
+        match self.unit() {
+            "" => self.name().to_string(),
+            unit => format!("{} ({})", self.name(), unit),
+        }
+    }
+
+    // Name with the unit as a suffix, for JSON keys.
+    pub fn key(&self) -> String {
+        match self.unit() {
+            "" => self.name().to_string(),
+            unit => format!("{}_{}", self.name(), unit),
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Column> {
+        Column::ALL.into_iter().find(|c| c.name() == name)
+    }
+
+    pub fn value(&self, p: &Point) -> Value {
+        Value::Number(match self {
+            Column::Format => return Value::Text(p.format.map_or("", |f| f.name)),
+            Column::Diameter => p.diameter,
+            Column::Thickness => p.thickness,
+            Column::FocalLength => p.focal_length,
+            Column::Projection => p.projection,
+            Column::Wavelength => p.wavelength,
+            Column::RayleighFactor => p.rayleigh_factor,
+            Column::SubjectDistance => p.subject_distance,
+            Column::Magnification => p.magnification(),
+            Column::FNumber => p.fnumber(),
+            Column::StopsFromF32 => crate::delta_thirds(32., p.fnumber()),
+            Column::ExposureFactor => crate::exposure_factor(32., p.fnumber()),
+            Column::ViewAngle => 2. * p.viewangle(),
+            Column::CoverageDiameter => 2. * p.coverage_radius(),
+            Column::NeededFocalLength => p.needed_focallength(),
+            Column::OptimalDiameter => p.optimalsize(),
+            Column::Vignetting => p.vignetting().0,
+            Column::VignettingStops => crate::stop_equivalent(p.vignetting().0),
+            Column::VignettingAngle => p.vignetting().1,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Output {
+    Csv,
+    Tsv,
+    Json,
+}
+
+// Write the sweep row by row, without keeping it in memory.
+pub fn write<W: Write>(
+    w: &mut W,
+    sweep: &Sweep,
+    columns: &[Column],
+    output: Output,
+) -> io::Result<()> {
+    match output {
+        Output::Csv => write_delimited(w, sweep, columns, ','),
+        Output::Tsv => write_delimited(w, sweep, columns, '\t'),
+        Output::Json => write_json(w, sweep, columns),
+    }
+}
+
+fn write_delimited<W: Write>(
+    w: &mut W,
+    sweep: &Sweep,
+    columns: &[Column],
+    sep: char,
+) -> io::Result<()> {
+    for (i, c) in columns.iter().enumerate() {
+        if i > 0 {
+            write!(w, "{sep}")?;
+        }
+        write!(w, "{}", c.label())?;
+    }
+    writeln!(w)?;
+    for p in sweep.iter() {
+        for (i, c) in columns.iter().enumerate() {
+            if i > 0 {
+                write!(w, "{sep}")?;
+            }
+            match c.value(&p) {
+                Value::Number(x) => write!(w, "{x}")?,
+                // Format names have inch marks, so quote them.
+                Value::Text(s) if s.contains(['"', sep]) => {
+                    write!(w, "\"{}\"", s.replace('"', "\"\""))?
+                }
+                Value::Text(s) => write!(w, "{s}")?,
+            }
+        }
+        writeln!(w)?;
+    }
+    Ok(())
+}
+
+fn write_json<W: Write>(w: &mut W, sweep: &Sweep, columns: &[Column]) -> io::Result<()> {
+    let keys: Vec<String> = columns.iter().map(Column::key).collect();
+    write!(w, "[")?;
+    for (n, p) in sweep.iter().enumerate() {
+        write!(w, "{}\n  {{", if n > 0 { "," } else { "" })?;
+        for (i, (c, key)) in columns.iter().zip(&keys).enumerate() {
+            if i > 0 {
+                write!(w, ", ")?;
+            }
+            match c.value(&p) {
+                // JSON has no infinity, use null for a subject at infinity.
+                Value::Number(x) if !x.is_finite() => write!(w, "\"{key}\": null")?,
+                Value::Number(x) => write!(w, "\"{key}\": {x}")?,
+                Value::Text(s) => write!(w, "\"{key}\": \"{}\"", s.replace('"', "\\\""))?,
+            }
+        }
+        write!(w, "}}")?;
+    }
+    writeln!(w, "\n]")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn range_includes_end() {
+        let v = Values::Range {
+            start: 25.,
+            end: 300.,
+            step: 5.,
+        }
+        .to_vec()
+        .unwrap();
+        assert_eq!(v.len(), 56);
+        assert_eq!(v[55], 300.);
+    }
+
+    #[test]
+    fn bad_ranges_are_refused() {
+        let range = |start, end, step| Values::Range { start, end, step }.to_vec();
+        assert!(range(300., 25., 5.).unwrap_err().contains("downwards"));
+        assert!(range(25., 300., 0.).is_err());
+        assert!(range(25., 300., -5.).is_err());
+        assert!(range(25., 300., f32::NAN).is_err());
+        assert!(range(1., f32::INFINITY, 1.).is_err());
+        assert!(range(1., 1e9, 1e-3).unwrap_err().contains("too long"));
+        assert_eq!(range(50., 50., 5.).unwrap(), [50.]);
+    }
+
+    #[test]
+    fn too_many_points_are_refused() {
+        let thousand = Values::Range {
+            start: 1.,
+            end: 1000.,
+            step: 1.,
+        };
+        let sweep = Sweep::new(Point::default())
+            .param(Param::FocalLength, &thousand)
+            .and_then(|s| s.param(Param::Diameter, &thousand))
+            .unwrap();
+        assert_eq!(sweep.len(), 1_000_000);
+        let e = sweep.param(Param::Thickness, &thousand).err().unwrap();
+        assert!(e.contains("more than 10000000 points"), "{e}");
+    }
+
+    #[test]
+    fn cartesian_product() {
+        let sweep = Sweep::new(Point::default())
+            .all_formats()
+            .param(Param::FocalLength, &Values::List(vec![25., 50., 75.]))
+            .unwrap();
+        let points: Vec<Point> = sweep.iter().collect();
+        assert_eq!(points.len(), sweep.len());
+        assert_eq!(points.len(), 13 * 3);
+        assert_eq!(points[1].focal_length, 50.);
+        assert_eq!(points[3].format.unwrap().name, "645");
+    }
+
+    #[test]
+    fn csv_quotes_inch_marks() {
+        let sweep =
+            Sweep::new(Point::default()).formats(vec![crate::formats::find("4x5").unwrap()]);
+        let mut out = Vec::new();
+        write(
+            &mut out,
+            &sweep,
+            &[Column::Format, Column::Projection],
+            Output::Csv,
+        )
+        .unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert_eq!(out, "format,projection (mm)\n\"4\"\"x5\"\"\",163\n");
+    }
+}